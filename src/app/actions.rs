@@ -15,12 +15,49 @@ pub enum Action {
     MoveUp,
     MoveDown,
     Save,
+    HistoryBack,
+    HistoryForward,
+    DiskUsage,
+    Rescan,
+    AddBookmark,
+    ShowBookmarks,
+    MoveBookmarkUp,
+    MoveBookmarkDown,
+    RenameBookmark,
+    DeleteBookmark,
+    Close,
+    GotoPath,
+    NewTab,
+    CloseTab,
+    NextTab,
+    PreviousTab,
+    Commander,
+    SwitchPane,
+    Copy,
+    ToggleMark,
+    MarkAll,
+    InvertMarks,
+    MarkGlob,
+    Delete,
+    Confirm,
+    MoveToTrash,
+    ToggleRecursive,
+    Cancel,
+    Mkdir,
+    Rename,
+    Move,
+    Properties,
+    EditProperty,
+    PreviewChanges,
+    Edit,
+    OpenWith,
+    Connections,
 }
 
 impl Action {
     /// All available actions
     pub fn iterator() -> Iter<'static, Action> {
-        static ACTIONS: [Action; 8] = [
+        static ACTIONS: [Action; 45] = [
             Action::Quit,
             Action::IncrementDelay,
             Action::DecrementDelay,
@@ -29,6 +66,43 @@ impl Action {
             Action::MoveUp,
             Action::MoveDown,
             Action::Save,
+            Action::HistoryBack,
+            Action::HistoryForward,
+            Action::DiskUsage,
+            Action::Rescan,
+            Action::AddBookmark,
+            Action::ShowBookmarks,
+            Action::MoveBookmarkUp,
+            Action::MoveBookmarkDown,
+            Action::RenameBookmark,
+            Action::DeleteBookmark,
+            Action::Close,
+            Action::GotoPath,
+            Action::NewTab,
+            Action::CloseTab,
+            Action::NextTab,
+            Action::PreviousTab,
+            Action::Commander,
+            Action::SwitchPane,
+            Action::Copy,
+            Action::ToggleMark,
+            Action::MarkAll,
+            Action::InvertMarks,
+            Action::MarkGlob,
+            Action::Delete,
+            Action::Confirm,
+            Action::MoveToTrash,
+            Action::ToggleRecursive,
+            Action::Cancel,
+            Action::Mkdir,
+            Action::Rename,
+            Action::Move,
+            Action::Properties,
+            Action::EditProperty,
+            Action::PreviewChanges,
+            Action::Edit,
+            Action::OpenWith,
+            Action::Connections,
         ];
         ACTIONS.iter()
     }
@@ -44,6 +118,43 @@ impl Action {
             Action::MoveUp => &[Key::Up, Key::Char('k')],
             Action::MoveDown => &[Key::Down, Key::Char('j')],
             Action::Save => &[Key::Char('s')],
+            Action::HistoryBack => &[Key::Char('H')],
+            Action::HistoryForward => &[Key::Char('L')],
+            Action::DiskUsage => &[Key::Char('u')],
+            Action::Rescan => &[Key::Char('r')],
+            Action::AddBookmark => &[Key::Char('b')],
+            Action::ShowBookmarks => &[Key::Char('B')],
+            Action::MoveBookmarkUp => &[Key::Char('K')],
+            Action::MoveBookmarkDown => &[Key::Char('J')],
+            Action::RenameBookmark => &[Key::Char('r')],
+            Action::DeleteBookmark => &[Key::Char('d')],
+            Action::Close => &[Key::Esc],
+            Action::GotoPath => &[Key::Char(':'), Key::Char('g')],
+            Action::NewTab => &[Key::Char('t')],
+            Action::CloseTab => &[Key::Char('x')],
+            Action::NextTab => &[Key::Char(']')],
+            Action::PreviousTab => &[Key::Char('[')],
+            Action::Commander => &[Key::Char('c')],
+            Action::SwitchPane => &[Key::Tab],
+            Action::Copy => &[Key::F5, Key::Char('y')],
+            Action::ToggleMark => &[Key::Char(' '), Key::Ins],
+            Action::MarkAll => &[Key::Char('a')],
+            Action::InvertMarks => &[Key::Char('i')],
+            Action::MarkGlob => &[Key::Char('*')],
+            Action::Delete => &[Key::Char('d'), Key::Delete],
+            Action::Confirm => &[Key::Char('y')],
+            Action::MoveToTrash => &[Key::Char('t')],
+            Action::ToggleRecursive => &[Key::Char('r')],
+            Action::Cancel => &[Key::Char('n'), Key::Esc],
+            Action::Mkdir => &[Key::Char('m')],
+            Action::Rename => &[Key::Char('r')],
+            Action::Move => &[Key::Char('M')],
+            Action::Properties => &[Key::Char('p')],
+            Action::EditProperty => &[Key::Char('e'), Key::Enter],
+            Action::PreviewChanges => &[Key::Char('s')],
+            Action::Edit => &[Key::Char('e')],
+            Action::OpenWith => &[Key::Char('o')],
+            Action::Connections => &[Key::Char('C')],
         }
     }
 }
@@ -60,6 +171,43 @@ impl Display for Action {
             Action::MoveUp => "Move up",
             Action::MoveDown => "Move down",
             Action::Save => "Save",
+            Action::HistoryBack => "History back",
+            Action::HistoryForward => "History forward",
+            Action::DiskUsage => "Disk usage",
+            Action::Rescan => "Rescan",
+            Action::AddBookmark => "Add bookmark",
+            Action::ShowBookmarks => "Bookmarks",
+            Action::MoveBookmarkUp => "Move bookmark up",
+            Action::MoveBookmarkDown => "Move bookmark down",
+            Action::RenameBookmark => "Rename bookmark",
+            Action::DeleteBookmark => "Delete bookmark",
+            Action::Close => "Close",
+            Action::GotoPath => "Go to path",
+            Action::NewTab => "New tab",
+            Action::CloseTab => "Close tab",
+            Action::NextTab => "Next tab",
+            Action::PreviousTab => "Previous tab",
+            Action::Commander => "Commander",
+            Action::SwitchPane => "Switch pane",
+            Action::Copy => "Copy",
+            Action::ToggleMark => "Toggle mark",
+            Action::MarkAll => "Mark all",
+            Action::InvertMarks => "Invert marks",
+            Action::MarkGlob => "Mark matching",
+            Action::Delete => "Delete",
+            Action::Confirm => "Confirm",
+            Action::MoveToTrash => "Move to trash",
+            Action::ToggleRecursive => "Toggle recursive",
+            Action::Cancel => "Cancel",
+            Action::Mkdir => "Make directory",
+            Action::Rename => "Rename",
+            Action::Move => "Move to",
+            Action::Properties => "Properties",
+            Action::EditProperty => "Edit field",
+            Action::PreviewChanges => "Preview changes",
+            Action::Edit => "Edit in $EDITOR",
+            Action::OpenWith => "Open with $PAGER",
+            Action::Connections => "Connections",
         };
         write!(f, "{}", str)
     }
@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Bookmark {
+    pub name: String,
+    pub url: String,
+}
+
+/// The user bookmarks, in the order displayed in the popup
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Bookmarks {
+    #[serde(default)]
+    bookmark: Vec<Bookmark>,
+}
+
+impl Bookmarks {
+    /// Load the bookmarks, a missing or broken file gives no bookmarks
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("🔖 can not read {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            warn!("🔖 invalid bookmarks file {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string(self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, content)
+    }
+
+    pub fn list(&self) -> &[Bookmark] {
+        self.bookmark.as_slice()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Bookmark> {
+        self.bookmark.get(index)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmark.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmark.is_empty()
+    }
+
+    /// Bookmark `url`, named after its last segment.
+    /// Returns `false` if the url is already bookmarked.
+    pub fn add(&mut self, url: &str) -> bool {
+        if self.bookmark.iter().any(|bookmark| bookmark.url == url) {
+            return false;
+        }
+        let name = url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty())
+            .unwrap_or(url)
+            .to_string();
+        self.bookmark.push(Bookmark {
+            name,
+            url: url.to_string(),
+        });
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Bookmark> {
+        (index < self.bookmark.len()).then(|| self.bookmark.remove(index))
+    }
+
+    pub fn rename(&mut self, index: usize, name: &str) {
+        if let Some(bookmark) = self.bookmark.get_mut(index) {
+            bookmark.name = name.to_string();
+        }
+    }
+
+    /// Swap the bookmark with the previous one, returns its new index
+    pub fn move_up(&mut self, index: usize) -> usize {
+        if index > 0 && index < self.bookmark.len() {
+            self.bookmark.swap(index, index - 1);
+            index - 1
+        } else {
+            index
+        }
+    }
+
+    /// Swap the bookmark with the next one, returns its new index
+    pub fn move_down(&mut self, index: usize) -> usize {
+        if index + 1 < self.bookmark.len() {
+            self.bookmark.swap(index, index + 1);
+            index + 1
+        } else {
+            index
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_add_named_after_last_segment() {
+        let mut bookmarks = Bookmarks::default();
+        assert!(bookmarks.add("http://host/warehouse/sales/"));
+        assert!(!bookmarks.add("http://host/warehouse/sales/"));
+        assert!(bookmarks.add("http://host/"));
+        assert_eq!(bookmarks.list()[0].name, "sales");
+        assert_eq!(bookmarks.list()[1].name, "host");
+    }
+
+    #[test]
+    fn should_reorder_rename_and_delete() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("http://host/a/");
+        bookmarks.add("http://host/b/");
+        bookmarks.add("http://host/c/");
+
+        assert_eq!(bookmarks.move_up(2), 1);
+        assert_eq!(bookmarks.move_down(2), 2);
+        assert_eq!(bookmarks.move_up(0), 0);
+        bookmarks.rename(0, "first");
+        let names = bookmarks
+            .list()
+            .iter()
+            .map(|b| b.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["first", "c", "b"]);
+
+        assert_eq!(bookmarks.remove(1).map(|b| b.name), Some("c".to_string()));
+        assert_eq!(bookmarks.remove(5), None);
+        assert_eq!(bookmarks.len(), 2);
+    }
+
+    #[test]
+    fn should_round_trip_toml() {
+        let mut bookmarks = Bookmarks::default();
+        bookmarks.add("http://host/a/");
+        let content = toml::to_string(&bookmarks).unwrap();
+        assert_eq!(toml::from_str::<Bookmarks>(&content).unwrap(), bookmarks);
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStdin, Command, ExitStatus, Stdio};
+
+use log::warn;
+use reqwest::Response;
+use serde::Deserialize;
+
+/// What to do once the external program exited
+#[derive(Clone, Debug)]
+pub enum AfterExit {
+    /// Upload the edited copy of `url` if it changed, `original` is the downloaded content
+    Upload {
+        url: String,
+        path: PathBuf,
+        original: Vec<u8>,
+    },
+    /// Nothing was written locally
+    Nothing,
+}
+
+/// A program run in the terminal, the TUI is suspended meanwhile
+#[derive(Debug)]
+pub struct External {
+    /// The program followed by its arguments
+    command: Vec<String>,
+    /// The remote file whose content is streamed to the standard input of the program
+    input: Option<Response>,
+    /// A program reading `input`, whose output is streamed to the program instead
+    filter: Option<Vec<String>>,
+    after: AfterExit,
+}
+
+impl External {
+    /// Edit the local copy of `url` with the editor of the user
+    pub fn edit(url: String, path: PathBuf, original: Vec<u8>) -> Self {
+        Self {
+            command: command_line(&editor(), &path),
+            input: None,
+            filter: None,
+            after: AfterExit::Upload {
+                url,
+                path,
+                original,
+            },
+        }
+    }
+
+    /// Stream the content of the remote file to the pager of the user,
+    /// through `filter` (a command reading its standard input) when given
+    pub fn open(content: Response, filter: Option<&str>) -> Self {
+        Self {
+            command: split(&pager()),
+            input: Some(content),
+            filter: filter.map(split),
+            after: AfterExit::Nothing,
+        }
+    }
+
+    pub fn program(&self) -> &str {
+        self.command.first().map(String::as_str).unwrap_or_default()
+    }
+
+    pub fn after(&self) -> &AfterExit {
+        &self.after
+    }
+
+    /// Run the program until it exits, it blocks so it is called out of the async runtime
+    pub fn run(&mut self) -> std::io::Result<ExitStatus> {
+        let (program, args) = split_program(&self.command)?;
+        let mut command = Command::new(program);
+        command.args(args);
+        let input = match self.input.take() {
+            Some(input) => input,
+            None => return command.status(),
+        };
+        if let Some(filter) = &self.filter {
+            let (program, args) = split_program(filter)?;
+            let mut filter = Command::new(program)
+                .args(args)
+                .stdin(Stdio::piped())
+                .stdout(Stdio::piped())
+                .spawn()?;
+            if let Some(output) = filter.stdout.take() {
+                command.stdin(output);
+            }
+            let spawned = command.spawn();
+            // the pager holds the output of the filter now, it must be its only reader
+            drop(command);
+            let mut pager = match spawned {
+                Ok(pager) => pager,
+                Err(e) => {
+                    filter.kill()?;
+                    filter.wait()?;
+                    return Err(e);
+                }
+            };
+            let streamed = stream(input, filter.stdin.take());
+            // the programs are waited for even if the download failed, they see a truncated file
+            let status = pager.wait();
+            // the filter gets a broken pipe if the pager exited first
+            filter.wait()?;
+            streamed.and(status)
+        } else {
+            let mut child = command.stdin(Stdio::piped()).spawn()?;
+            let streamed = stream(input, child.stdin.take());
+            let status = child.wait();
+            streamed.and(status)
+        }
+    }
+}
+
+/// The program of a command line and its arguments, a configured command may be empty
+fn split_program(command: &[String]) -> std::io::Result<(&String, &[String])> {
+    command
+        .split_first()
+        .ok_or_else(|| std::io::Error::new(ErrorKind::InvalidInput, "empty command"))
+}
+
+/// Write the content of `response` to `stdin` as it is received, then close it.
+/// The program may exit before reading everything, e.g. a pager quit early.
+fn stream(mut response: Response, stdin: Option<ChildStdin>) -> std::io::Result<()> {
+    let mut stdin = match stdin {
+        Some(stdin) => stdin,
+        None => return Ok(()),
+    };
+    let runtime = tokio::runtime::Handle::current();
+    let written: std::io::Result<()> = runtime.block_on(async {
+        while let Some(chunk) = response.chunk().await.map_err(std::io::Error::other)? {
+            stdin.write_all(&chunk)?;
+        }
+        Ok(())
+    });
+    match written {
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        written => written,
+    }
+}
+
+/// The editor of the user: `$VISUAL`, `$EDITOR`, or the platform default
+pub fn editor() -> String {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                String::from("notepad")
+            } else {
+                String::from("vi")
+            }
+        })
+}
+
+/// The pager of the user: `$PAGER`, or the platform default
+pub fn pager() -> String {
+    std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                String::from("more")
+            } else {
+                String::from("less")
+            }
+        })
+}
+
+/// The commands to open files with, by extension, e.g. `json = "jq ."`,
+/// they read the file on their standard input
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct OpenWith {
+    #[serde(default)]
+    extension: HashMap<String, String>,
+}
+
+impl OpenWith {
+    /// Load the commands, a missing or broken file gives no command
+    pub fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                warn!("📄 can not read {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        toml::from_str(&content)
+            .map(Self::without_empty_commands)
+            .unwrap_or_else(|e| {
+                warn!("📄 invalid open with file {}: {}", path.display(), e);
+                Self::default()
+            })
+    }
+
+    /// Drop the blank commands, the files of their extension are opened with the pager
+    fn without_empty_commands(mut self) -> Self {
+        self.extension.retain(|extension, command| {
+            let blank = command.trim().is_empty();
+            if blank {
+                warn!("📄 empty command for the {} files, ignored", extension);
+            }
+            !blank
+        });
+        self
+    }
+
+    /// The command configured for the extension of `name`, ignoring the case
+    pub fn command(&self, name: &str) -> Option<&str> {
+        let extension = Path::new(name).extension()?.to_str()?.to_lowercase();
+        self.extension.get(&extension).map(String::as_str)
+    }
+}
+
+/// Split a configured command (like `code --wait`) on whitespaces
+fn split(command: &str) -> Vec<String> {
+    command.split_whitespace().map(String::from).collect()
+}
+
+/// Split a configured command on whitespaces and append `path`
+fn command_line(command: &str, path: &Path) -> Vec<String> {
+    let mut command_line = split(command);
+    command_line.push(path.to_string_lossy().to_string());
+    command_line
+}
+
+/// A local path to hold a copy of the remote file `name`, in a directory of this process
+pub fn temp_path(name: &str) -> std::io::Result<PathBuf> {
+    let dir = std::env::temp_dir().join(format!("hdfs-proxy-tui-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir.join(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_append_path_to_command() {
+        let path = Path::new("data.csv");
+        assert_eq!(command_line("vim", path), vec!["vim", "data.csv"]);
+        assert_eq!(
+            command_line(" code  --wait ", path),
+            vec!["code", "--wait", "data.csv"]
+        );
+    }
+
+    #[test]
+    fn should_find_command_by_extension() {
+        let open_with: OpenWith = toml::from_str(
+            r#"
+            [extension]
+            parquet = "parquet-tools cat"
+            json = "jq ."
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            open_with.command("part-0001.PARQUET"),
+            Some("parquet-tools cat")
+        );
+        assert_eq!(open_with.command("data.json"), Some("jq ."));
+        assert_eq!(open_with.command("README"), None);
+        assert_eq!(open_with.command("data.csv"), None);
+    }
+
+    #[test]
+    fn should_reject_empty_commands() {
+        let open_with: OpenWith = toml::from_str(
+            r#"
+            [extension]
+            csv = " "
+            json = "jq ."
+            "#,
+        )
+        .unwrap();
+        let open_with = open_with.without_empty_commands();
+        assert_eq!(open_with.command("data.csv"), None);
+        assert_eq!(open_with.command("data.json"), Some("jq ."));
+
+        let mut external = External {
+            command: Vec::new(),
+            input: None,
+            filter: None,
+            after: AfterExit::Nothing,
+        };
+        assert_eq!(external.program(), "");
+        let error = external.run().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+/// Where the cursor was in a directory
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Cursor {
+    pub index: i32,
+    pub frame_start: usize,
+    pub frame_end: usize,
+}
+
+/// The visited urls, like a web browser, and the cursor of each visited directory
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    back: Vec<String>,
+    forward: Vec<String>,
+    cursors: HashMap<String, Cursor>,
+}
+
+impl History {
+    /// We are leaving `from` for a new location, the forward history is lost
+    pub fn visit(&mut self, from: String) {
+        if self.back.last() != Some(&from) {
+            self.back.push(from);
+        }
+        self.forward.clear();
+    }
+
+    /// The previous location, `current` becomes reachable with `forward`
+    pub fn back(&mut self, current: String) -> Option<String> {
+        let url = self.back.pop()?;
+        self.forward.push(current);
+        Some(url)
+    }
+
+    /// The next location, `current` becomes reachable with `back`
+    pub fn forward(&mut self, current: String) -> Option<String> {
+        let url = self.forward.pop()?;
+        self.back.push(current);
+        Some(url)
+    }
+
+    pub fn remember(&mut self, url: &str, cursor: Cursor) {
+        self.cursors.insert(url.to_string(), cursor);
+    }
+
+    pub fn cursor(&self, url: &str) -> Option<Cursor> {
+        self.cursors.get(url).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_go_back_and_forward() {
+        let mut history = History::default();
+        history.visit("/a/".to_string());
+        history.visit("/a/b/".to_string());
+
+        assert_eq!(
+            history.back("/a/b/c/".to_string()),
+            Some("/a/b/".to_string())
+        );
+        assert_eq!(history.back("/a/b/".to_string()), Some("/a/".to_string()));
+        assert_eq!(history.back("/a/".to_string()), None);
+        assert_eq!(
+            history.forward("/a/".to_string()),
+            Some("/a/b/".to_string())
+        );
+        assert_eq!(
+            history.forward("/a/b/".to_string()),
+            Some("/a/b/c/".to_string())
+        );
+        assert_eq!(history.forward("/a/b/c/".to_string()), None);
+    }
+
+    #[test]
+    fn should_clear_forward_on_visit() {
+        let mut history = History::default();
+        history.visit("/a/".to_string());
+        history.back("/b/".to_string());
+        history.visit("/a/".to_string());
+        assert_eq!(history.forward("/c/".to_string()), None);
+    }
+
+    #[test]
+    fn should_remember_cursor() {
+        let mut history = History::default();
+        let cursor = Cursor {
+            index: 12,
+            frame_start: 5,
+            frame_end: 15,
+        };
+        history.remember("/a/", cursor);
+        assert_eq!(history.cursor("/a/"), Some(cursor));
+        assert_eq!(history.cursor("/b/"), None);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
+use log::{error, info};
+
+use crate::app::state::Item;
+
+/// The pane of the commander that has the focus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pane {
+    Local,
+    Remote,
+}
+
+/// A pane browsing the local filesystem
+#[derive(Clone, Debug)]
+pub struct LocalPane {
+    dir: PathBuf,
+    /// Entries of `dir`, directories have a size of -1 like the remote items
+    items: Vec<Item>,
+    index: usize,
+    /// Names of the marked entries of `dir`
+    marked: HashSet<String>,
+}
+
+impl LocalPane {
+    pub fn new(dir: PathBuf) -> Self {
+        let mut pane = Self {
+            dir,
+            items: Vec::new(),
+            index: 0,
+            marked: HashSet::new(),
+        };
+        pane.refresh();
+        pane
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn selected(&self) -> Option<&Item> {
+        self.items.get(self.index)
+    }
+
+    /// Read the directory again, keeping the selection when possible
+    pub fn refresh(&mut self) {
+        match read_dir(&self.dir) {
+            Ok(items) => self.items = items,
+            Err(e) => {
+                error!("📁 can not read {}: {}", self.dir.display(), e);
+                self.items = Vec::new();
+            }
+        }
+        self.index = self.index.min(self.items.len().saturating_sub(1));
+        let items = &self.items;
+        self.marked
+            .retain(|name| items.iter().any(|item| &item.name == name));
+    }
+
+    pub fn move_up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.index = (self.index + 1).min(self.items.len().saturating_sub(1));
+    }
+
+    pub fn step_into(&mut self) {
+        match self.selected() {
+            Some(item) if item.size == -1 => {
+                self.dir = self.dir.join(&item.name);
+                self.index = 0;
+                self.marked.clear();
+                self.refresh();
+            }
+            Some(_) => info!("📁 only directories can be opened in the local pane"),
+            None => {}
+        }
+    }
+
+    pub fn back_to_previous(&mut self) {
+        let came_from = self
+            .dir
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string());
+        if let Some(parent) = self.dir.parent() {
+            self.dir = parent.to_path_buf();
+            self.marked.clear();
+            self.refresh();
+            // Land on the directory we came from
+            self.index = came_from
+                .and_then(|name| self.items.iter().position(|item| item.name == name))
+                .unwrap_or(0);
+        }
+    }
+}
+
+impl LocalPane {
+    pub fn is_marked(&self, name: &str) -> bool {
+        self.marked.contains(name)
+    }
+
+    pub fn count_marked(&self) -> usize {
+        self.marked.len()
+    }
+
+    /// Mark or unmark the selected entry, then select the next one
+    pub fn toggle_mark(&mut self) {
+        let name = match self.selected() {
+            Some(item) => item.name.clone(),
+            None => return,
+        };
+        if !self.marked.remove(&name) {
+            self.marked.insert(name);
+        }
+        self.move_down();
+    }
+
+    pub fn mark_all(&mut self) {
+        self.marked
+            .extend(self.items.iter().map(|item| item.name.clone()));
+    }
+
+    pub fn invert_marks(&mut self) {
+        self.marked = self
+            .items
+            .iter()
+            .map(|item| item.name.clone())
+            .filter(|name| !self.marked.contains(name))
+            .collect();
+    }
+
+    /// Mark the entries matching the glob `pattern`, returns the number of matching entries
+    pub fn mark_glob(&mut self, pattern: &Pattern) -> usize {
+        let names = self
+            .items
+            .iter()
+            .filter(|item| pattern.matches(&item.name))
+            .map(|item| item.name.clone())
+            .collect::<Vec<_>>();
+        let count = names.len();
+        self.marked.extend(names);
+        count
+    }
+
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+    }
+
+    /// The marked entries, in the listing order, or else the selected one
+    pub fn targets(&self) -> Vec<&Item> {
+        let marked = self
+            .items
+            .iter()
+            .filter(|item| self.marked.contains(&item.name))
+            .collect::<Vec<_>>();
+        if marked.is_empty() {
+            self.selected().into_iter().collect()
+        } else {
+            marked
+        }
+    }
+}
+
+/// List a local directory, directories first then sorted by name
+fn read_dir(dir: &Path) -> std::io::Result<Vec<Item>> {
+    let mut items = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let size = match entry.metadata() {
+                Ok(metadata) if metadata.is_dir() => -1,
+                Ok(metadata) => metadata.len() as i64,
+                Err(_) => 0,
+            };
+            Item { name, size }
+        })
+        .collect::<Vec<_>>();
+    items.sort_by(|a, b| {
+        (a.size != -1)
+            .cmp(&(b.size != -1))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(items)
+}
+
+/// The Midnight Commander like layout: the local filesystem next to the remote
+#[derive(Clone, Debug)]
+pub struct Commander {
+    pub local: LocalPane,
+    pub focus: Pane,
+}
+
+impl Commander {
+    pub fn new(dir: PathBuf) -> Self {
+        Self {
+            local: LocalPane::new(dir),
+            focus: Pane::Local,
+        }
+    }
+
+    pub fn switch_focus(&mut self) {
+        self.focus = match self.focus {
+            Pane::Local => Pane::Remote,
+            Pane::Remote => Pane::Local,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pane(names: &[&str]) -> LocalPane {
+        let items = names
+            .iter()
+            .map(|name| Item {
+                name: name.to_string(),
+                size: if name.contains('.') { 12 } else { -1 },
+            })
+            .collect();
+        LocalPane {
+            dir: PathBuf::from("/home/alice"),
+            items,
+            index: 0,
+            marked: HashSet::new(),
+        }
+    }
+
+    fn target_names(pane: &LocalPane) -> Vec<&str> {
+        pane.targets()
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn should_mark_local_entries() {
+        let mut pane = pane(&["logs", "a.csv", "b.csv", "c.txt"]);
+        assert_eq!(target_names(&pane), vec!["logs"]);
+
+        assert_eq!(pane.mark_glob(&Pattern::new("*.csv").unwrap()), 2);
+        assert_eq!(target_names(&pane), vec!["a.csv", "b.csv"]);
+
+        pane.invert_marks();
+        assert_eq!(target_names(&pane), vec!["logs", "c.txt"]);
+
+        pane.toggle_mark();
+        assert_eq!(target_names(&pane), vec!["c.txt"]);
+        assert_eq!(pane.index(), 1);
+
+        pane.clear_marks();
+        assert_eq!(target_names(&pane), vec!["a.csv"]);
+    }
+}
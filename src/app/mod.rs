@@ -1,14 +1,37 @@
-use log::{debug, error, warn};
+use std::fmt;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+
+use glob::Pattern;
+use log::{debug, error, info, warn};
+use reqwest::Response;
 
 use self::actions::Actions;
-use self::state::AppState;
+use self::bookmarks::Bookmarks;
+use self::external::{AfterExit, External, OpenWith};
+use self::local::{Commander, LocalPane, Pane};
+use self::popup::Popup;
+use self::prompt::{complete_path, moved_url, renamed_url, split_parent, Prompt, PromptKind};
+use self::properties::{FileStatus, Properties};
+use self::state::{AppState, Item};
+use self::usage::UsageNode;
 use crate::app::actions::Action;
+use crate::config::{self, Connection, Settings};
 use crate::inputs::key::Key;
-use crate::io::IoEvent;
+use crate::io::{BatchOp, IoEvent};
+use crate::request::{self, get_item_list, split_origin, RequestError};
 
 pub mod actions;
+pub mod bookmarks;
+pub mod external;
+pub mod history;
+pub mod local;
+pub mod popup;
+pub mod prompt;
+pub mod properties;
 pub mod state;
 pub mod ui;
+pub mod usage;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum AppReturn {
@@ -24,74 +47,911 @@ pub struct App {
     actions: Actions,
     /// State
     is_loading: bool,
-    state: AppState,
+    /// Each tab has its own state
+    tabs: Vec<AppState>,
+    /// The index of the displayed tab
+    tab: usize,
+    bookmarks: Bookmarks,
+    /// Popup over the body, it owns the contextual actions
+    popup: Option<Popup>,
+    /// Text input over the body, it owns the keyboard
+    prompt: Option<Prompt>,
+    /// The local pane, when the commander layout is displayed
+    commander: Option<Commander>,
+    /// Progress of the running batch: (done, total)
+    batch: Option<(usize, usize)>,
+    /// The program to run with the TUI suspended
+    external: Option<External>,
+    open_with: OpenWith,
+    /// Where the files are saved
+    download_dir: PathBuf,
+    /// The connections to switch to
+    connections: Vec<Connection>,
+    /// The name of the active connection
+    connection: String,
 }
 
 impl App {
-    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoEvent>) -> Self {
+    pub fn new(io_tx: tokio::sync::mpsc::Sender<IoEvent>, settings: &Settings) -> Self {
         let actions = vec![Action::Quit].into();
         let is_loading = false;
-        let state = AppState::default();
+        let tabs = vec![AppState::default()];
+        let bookmarks = Bookmarks::load(&config::bookmarks_path());
 
         Self {
             io_tx,
             actions,
             is_loading,
-            state,
+            tabs,
+            tab: 0,
+            bookmarks,
+            popup: None,
+            prompt: None,
+            commander: None,
+            batch: None,
+            external: None,
+            open_with: OpenWith::load(&config::open_with_path()),
+            download_dir: settings.download_dir.clone(),
+            connections: settings.connections.clone(),
+            connection: settings.connection.name.clone(),
         }
     }
 
     /// Handle a user action
     pub async fn do_action(&mut self, key: Key) -> AppReturn {
-        if let Some(action) = self.actions.find(key) {
+        if self.prompt.is_some() {
+            self.do_prompt_input(key).await;
+            return AppReturn::Continue;
+        }
+        if let Some(action) = self.actions.find(key).copied() {
             debug!("Run action [{:?}]", action);
-            match action {
-                Action::Quit => AppReturn::Exit,
-                // IncrementDelay and DecrementDelay is handled in the UI thread
-                Action::IncrementDelay => {
-                    self.state.increment_delay();
-                    AppReturn::Continue
+            let result = match self.popup.clone() {
+                Some(popup) => self.do_popup_action(popup, action).await,
+                None => self.do_browse_action(action).await,
+            };
+            self.update_actions();
+            result
+        } else {
+            warn!("No action accociated to {}", key);
+            AppReturn::Continue
+        }
+    }
+
+    async fn do_browse_action(&mut self, action: Action) -> AppReturn {
+        match action {
+            Action::Quit => AppReturn::Exit,
+            // IncrementDelay and DecrementDelay is handled in the UI thread
+            Action::IncrementDelay => {
+                self.state().increment_delay();
+                AppReturn::Continue
+            }
+            // Note, that we clamp the duration, so we stay >= 0
+            Action::DecrementDelay => {
+                self.state().decrement_delay();
+                AppReturn::Continue
+            }
+
+            Action::BackToPreviours => {
+                if let Some(local) = self.focused_local() {
+                    local.back_to_previous();
+                } else if let Some(usage) = self.state().usage_mut() {
+                    usage.back_to_previous();
+                } else if let Err(e) = self.state().back_to_previours().await {
+                    self.show_error(e);
                 }
-                // Note, that we clamp the duration, so we stay >= 0
-                Action::DecrementDelay => {
-                    self.state.decrement_delay();
-                    AppReturn::Continue
+                AppReturn::Continue
+            }
+
+            Action::StepInto => {
+                if let Some(local) = self.focused_local() {
+                    local.step_into();
+                } else if let Some(usage) = self.state().usage_mut() {
+                    usage.step_into();
+                } else if let Err(e) = self.state().step_into().await {
+                    self.show_error(e);
                 }
+                AppReturn::Continue
+            }
 
-                Action::BackToPreviours => {
-                    self.state.back_to_previours().await;
-                    AppReturn::Continue
+            Action::MoveUp => {
+                if let Some(local) = self.focused_local() {
+                    local.move_up();
+                } else if let Some(usage) = self.state().usage_mut() {
+                    usage.move_up();
+                } else {
+                    self.state().move_up();
                 }
+                AppReturn::Continue
+            }
 
-                Action::StepInto => {
-                    self.state.step_into().await;
-                    AppReturn::Continue
+            Action::MoveDown => {
+                if let Some(local) = self.focused_local() {
+                    local.move_down();
+                } else if let Some(usage) = self.state().usage_mut() {
+                    usage.move_down();
+                } else {
+                    self.state().move_down();
+                }
+                AppReturn::Continue
+            }
+            Action::Save => {
+                self.save_targets().await;
+                AppReturn::Continue
+            }
+            Action::ToggleMark => {
+                if let Some(local) = self.focused_local() {
+                    local.toggle_mark();
+                } else {
+                    self.state().toggle_mark();
+                }
+                AppReturn::Continue
+            }
+            Action::MarkAll => {
+                if let Some(local) = self.focused_local() {
+                    local.mark_all();
+                } else {
+                    self.state().mark_all();
+                }
+                AppReturn::Continue
+            }
+            Action::InvertMarks => {
+                if let Some(local) = self.focused_local() {
+                    local.invert_marks();
+                } else {
+                    self.state().invert_marks();
+                }
+                AppReturn::Continue
+            }
+            Action::MarkGlob => {
+                self.prompt = Some(Prompt::new(PromptKind::MarkGlob, "*"));
+                AppReturn::Continue
+            }
+            Action::Delete => {
+                let urls = self.targets();
+                if !urls.is_empty() {
+                    self.popup = Some(Popup::ConfirmDelete {
+                        urls,
+                        recursive: false,
+                    });
+                }
+                AppReturn::Continue
+            }
+            Action::Properties => {
+                match self.current_tab().selected_url() {
+                    Some(url) => self.dispatch(IoEvent::LoadProperties(url)).await,
+                    None => info!("🔐 select an entry to show its properties"),
+                }
+                AppReturn::Continue
+            }
+            Action::Edit => {
+                match self.current_tab().selected_url() {
+                    Some(url) if !url.ends_with('/') => self.dispatch(IoEvent::Edit(url)).await,
+                    _ => info!("🖊️ select a file to edit"),
+                }
+                AppReturn::Continue
+            }
+            Action::OpenWith => {
+                match self.current_tab().selected_url() {
+                    Some(url) if !url.ends_with('/') => {
+                        let name = url.rsplit('/').next().unwrap_or_default();
+                        let filter = self.open_with.command(name).map(String::from);
+                        self.dispatch(IoEvent::Open(url, filter)).await;
+                    }
+                    _ => info!("📄 select a file to open"),
+                }
+                AppReturn::Continue
+            }
+            Action::Mkdir => {
+                self.prompt = Some(Prompt::new(PromptKind::Mkdir, ""));
+                AppReturn::Continue
+            }
+            Action::Rename => {
+                match self.current_tab().selected_item() {
+                    Some(item) => {
+                        let name = item.name.clone();
+                        self.prompt = Some(Prompt::new(PromptKind::Rename, &name));
+                    }
+                    None => info!("✏️ select an entry to rename"),
+                }
+                AppReturn::Continue
+            }
+            Action::Move => {
+                if let Some(current_dir) = self.current_tab().current_dir() {
+                    let (_, path) = split_origin(&current_dir);
+                    self.prompt = Some(Prompt::new(PromptKind::Move, path));
+                }
+                AppReturn::Continue
+            }
+            Action::HistoryBack => {
+                if let Err(e) = self.state().history_back().await {
+                    self.show_error(e);
+                }
+                AppReturn::Continue
+            }
+            Action::HistoryForward => {
+                if let Err(e) = self.state().history_forward().await {
+                    self.show_error(e);
                 }
+                AppReturn::Continue
+            }
+            Action::DiskUsage => {
+                self.toggle_usage().await;
+                AppReturn::Continue
+            }
+            Action::Rescan => {
+                if let Some(usage) = self.state().usage_mut() {
+                    usage.rescan();
+                    let root_url = usage.root_url().to_string();
+                    self.dispatch(IoEvent::ScanUsage(root_url)).await;
+                }
+                AppReturn::Continue
+            }
+            Action::AddBookmark => {
+                self.add_bookmark();
+                AppReturn::Continue
+            }
+            Action::ShowBookmarks => {
+                self.popup = Some(Popup::Bookmarks { index: 0 });
+                AppReturn::Continue
+            }
+            Action::Connections => {
+                let index = self
+                    .connections
+                    .iter()
+                    .position(|connection| connection.name == self.connection)
+                    .unwrap_or(0);
+                self.popup = Some(Popup::Connections { index });
+                AppReturn::Continue
+            }
+            Action::NewTab => {
+                self.new_tab();
+                AppReturn::Continue
+            }
+            Action::CloseTab => {
+                self.close_tab();
+                AppReturn::Continue
+            }
+            Action::NextTab => {
+                self.next_tab();
+                AppReturn::Continue
+            }
+            Action::PreviousTab => {
+                self.previous_tab();
+                AppReturn::Continue
+            }
+            Action::Commander => {
+                self.toggle_commander();
+                AppReturn::Continue
+            }
+            Action::SwitchPane => {
+                if let Some(commander) = self.commander.as_mut() {
+                    commander.switch_focus();
+                }
+                AppReturn::Continue
+            }
+            Action::Copy => {
+                self.copy().await;
+                AppReturn::Continue
+            }
+            Action::GotoPath => {
+                if let Some(current_dir) = self.state().current_dir() {
+                    let (_, path) = split_origin(&current_dir);
+                    self.prompt = Some(Prompt::new(PromptKind::GotoPath, path));
+                }
+                AppReturn::Continue
+            }
+            _ => AppReturn::Continue,
+        }
+    }
 
-                Action::MoveUp => {
-                    self.state.move_up();
-                    AppReturn::Continue
+    async fn do_popup_action(&mut self, popup: Popup, action: Action) -> AppReturn {
+        match popup {
+            Popup::Bookmarks { index } => self.do_bookmarks_action(index, action).await,
+            Popup::Connections { index } => self.do_connections_action(index, action).await,
+            Popup::ConfirmDelete { urls, recursive } => {
+                self.do_confirm_delete_action(urls, recursive, action).await
+            }
+            Popup::Properties(properties) => self.do_properties_action(properties, action).await,
+            Popup::Error(_) => {
+                if action == Action::Close {
+                    self.popup = None;
                 }
+            }
+        }
+        AppReturn::Continue
+    }
+
+    async fn do_confirm_delete_action(
+        &mut self,
+        urls: Vec<String>,
+        recursive: bool,
+        action: Action,
+    ) {
+        let ops = match action {
+            Action::Confirm => urls
+                .into_iter()
+                .map(|url| BatchOp::Delete { url, recursive })
+                .collect::<Vec<_>>(),
+            Action::MoveToTrash => urls
+                .into_iter()
+                .map(|url| BatchOp::Trash { url })
+                .collect::<Vec<_>>(),
+            Action::ToggleRecursive => {
+                self.popup = Some(Popup::ConfirmDelete {
+                    urls,
+                    recursive: !recursive,
+                });
+                return;
+            }
+            Action::Cancel => {
+                info!("🗑️ deletion cancelled");
+                self.popup = None;
+                return;
+            }
+            _ => return,
+        };
+        self.popup = None;
+        self.state().clear_marks();
+        self.dispatch(IoEvent::Batch(ops)).await;
+    }
 
-                Action::MoveDown => {
-                    self.state.move_down();
-                    AppReturn::Continue
+    async fn do_bookmarks_action(&mut self, index: usize, action: Action) {
+        let last = self.bookmarks.len().saturating_sub(1);
+        let index = match action {
+            Action::MoveUp => index.saturating_sub(1),
+            Action::MoveDown => (index + 1).min(last),
+            Action::MoveBookmarkUp => {
+                let index = self.bookmarks.move_up(index);
+                self.save_bookmarks();
+                index
+            }
+            Action::MoveBookmarkDown => {
+                let index = self.bookmarks.move_down(index);
+                self.save_bookmarks();
+                index
+            }
+            Action::DeleteBookmark => {
+                if let Some(bookmark) = self.bookmarks.remove(index) {
+                    info!("🔖 bookmark {} deleted", bookmark.name);
+                    self.save_bookmarks();
                 }
-                Action::Save => {
-                    self.state.save_file().await;
-                    AppReturn::Continue
+                index.min(self.bookmarks.len().saturating_sub(1))
+            }
+            Action::RenameBookmark => {
+                if let Some(bookmark) = self.bookmarks.get(index) {
+                    let prompt = Prompt::new(PromptKind::RenameBookmark(index), &bookmark.name);
+                    self.prompt = Some(prompt);
                 }
+                index
+            }
+            Action::StepInto => {
+                self.popup = None;
+                if let Some(bookmark) = self.bookmarks.get(index).cloned() {
+                    info!("🔖 jump to {}", bookmark.url);
+                    if let Err(e) = self.state().navigate(bookmark.url).await {
+                        self.show_error(e);
+                    }
+                }
+                return;
+            }
+            Action::Close => {
+                self.popup = None;
+                return;
             }
+            _ => index,
+        };
+        self.popup = Some(Popup::Bookmarks { index });
+    }
+
+    async fn do_connections_action(&mut self, index: usize, action: Action) {
+        let index = match action {
+            Action::MoveUp => index.saturating_sub(1),
+            Action::MoveDown => (index + 1).min(self.connections.len().saturating_sub(1)),
+            Action::StepInto => {
+                self.popup = None;
+                if let Some(connection) = self.connections.get(index).cloned() {
+                    info!("🔌 switching to {}", connection.name);
+                    self.dispatch(IoEvent::Connect(Box::new(connection))).await;
+                }
+                return;
+            }
+            Action::Close => {
+                self.popup = None;
+                return;
+            }
+            _ => index,
+        };
+        self.popup = Some(Popup::Connections { index });
+    }
+
+    async fn do_properties_action(&mut self, mut properties: Properties, action: Action) {
+        match action {
+            Action::MoveUp => properties.move_up(),
+            Action::MoveDown => properties.move_down(),
+            Action::EditProperty => {
+                let index = properties.index();
+                let value = &properties.values()[index];
+                self.prompt = Some(Prompt::new(PromptKind::EditProperty(index), value));
+            }
+            Action::PreviewChanges => {
+                properties.dry_run();
+            }
+            Action::Confirm => {
+                if let Some(changes) = properties.preview() {
+                    let event =
+                        IoEvent::SetProperties(properties.url().to_string(), changes.to_vec());
+                    self.popup = None;
+                    self.dispatch(event).await;
+                    return;
+                }
+            }
+            Action::Cancel => properties.cancel_preview(),
+            Action::Close => {
+                self.popup = None;
+                return;
+            }
+            _ => {}
+        }
+        self.popup = Some(Popup::Properties(properties));
+    }
+
+    /// The local copy of `url` is ready to be edited
+    pub fn edit_ready(&mut self, url: String, path: PathBuf, original: Vec<u8>) {
+        self.external = Some(External::edit(url, path, original));
+    }
+
+    /// The content of a remote file is ready to be streamed, through `filter` when configured
+    pub fn open_ready(&mut self, content: Response, filter: Option<String>) {
+        self.external = Some(External::open(content, filter.as_deref()));
+    }
+
+    /// The program to run, the UI loop gives it the terminal
+    pub fn take_external(&mut self) -> Option<External> {
+        self.external.take()
+    }
+
+    pub async fn external_done(&mut self, external: External, status: std::io::Result<ExitStatus>) {
+        let success = match status {
+            Ok(status) if status.success() => true,
+            Ok(status) => {
+                warn!("🖊️ {} exited with {}", external.program(), status);
+                false
+            }
+            Err(e) => {
+                error!("🖊️ can not run {}: {}", external.program(), e);
+                false
+            }
+        };
+        match external.after() {
+            AfterExit::Upload {
+                url,
+                path,
+                original,
+            } => {
+                if success {
+                    let event = IoEvent::UploadEdited {
+                        url: url.clone(),
+                        path: path.clone(),
+                        original: original.clone(),
+                    };
+                    self.dispatch(event).await;
+                } else {
+                    info!(
+                        "🖊️ {} not uploaded, the local copy is {}",
+                        url,
+                        path.display()
+                    );
+                }
+            }
+            AfterExit::Nothing => {}
+        }
+    }
+
+    /// Open the properties dialog, unless something else was opened meanwhile
+    pub fn properties_loaded(&mut self, url: String, status: FileStatus) {
+        if self.popup.is_none() && self.prompt.is_none() {
+            self.popup = Some(Popup::Properties(Properties::new(url, status)));
+            self.update_actions();
+        }
+    }
+
+    /// A prompt is displayed, keys are used to edit its input
+    async fn do_prompt_input(&mut self, key: Key) {
+        match key {
+            Key::Enter => {
+                if let Some(prompt) = self.prompt.take() {
+                    self.submit_prompt(prompt).await;
+                }
+            }
+            Key::Esc => self.prompt = None,
+            Key::Tab => self.complete_prompt().await,
+            Key::Backspace => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.pop();
+                }
+            }
+            Key::Char(c) => {
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.push(c);
+                }
+            }
+            _ => warn!("No input accociated to {}", key),
+        }
+    }
+
+    async fn submit_prompt(&mut self, prompt: Prompt) {
+        match prompt.kind() {
+            PromptKind::RenameBookmark(index) => {
+                let name = prompt.input().trim();
+                if name.is_empty() {
+                    warn!("🔖 a bookmark needs a name");
+                } else {
+                    self.bookmarks.rename(index, name);
+                    self.save_bookmarks();
+                }
+            }
+            PromptKind::MarkGlob => match Pattern::new(prompt.input().trim()) {
+                Ok(pattern) => {
+                    let count = match self.focused_local() {
+                        Some(local) => local.mark_glob(&pattern),
+                        None => self.state().mark_glob(&pattern),
+                    };
+                    info!("✔️ {} entries match {}", count, pattern);
+                }
+                Err(e) => warn!("✔️ invalid pattern {}: {}", prompt.input(), e),
+            },
+            PromptKind::EditProperty(index) => {
+                if let Some(Popup::Properties(properties)) = self.popup.as_mut() {
+                    properties.set_value(index, prompt.input());
+                }
+            }
+            PromptKind::Mkdir => {
+                let path = prompt.input().trim();
+                if path.is_empty() {
+                    warn!("📁 a directory needs a name");
+                } else if let Some(mut url) = self.resolve_url(path) {
+                    if !url.ends_with('/') {
+                        url.push('/');
+                    }
+                    self.dispatch(IoEvent::Mkdir(url)).await;
+                }
+            }
+            PromptKind::Rename => {
+                let name = prompt.input().trim();
+                let from = self.current_tab().selected_url();
+                match from {
+                    _ if name.is_empty() || name.contains('/') => {
+                        warn!(
+                            "✏️ invalid name {:?}, use move to change the directory",
+                            name
+                        )
+                    }
+                    Some(from) => {
+                        let to = renamed_url(&from, name);
+                        self.dispatch(IoEvent::Rename(vec![(from, to)])).await;
+                    }
+                    None => {}
+                }
+            }
+            PromptKind::Move => {
+                if let Some(mut dir_url) = self.resolve_url(prompt.input().trim()) {
+                    if !dir_url.ends_with('/') {
+                        dir_url.push('/');
+                    }
+                    let moves = self
+                        .targets()
+                        .into_iter()
+                        .map(|from| {
+                            let to = moved_url(&from, &dir_url);
+                            (from, to)
+                        })
+                        .collect::<Vec<_>>();
+                    self.state().clear_marks();
+                    self.dispatch(IoEvent::Rename(moves)).await;
+                }
+            }
+            PromptKind::GotoPath => {
+                if let Some(url) = self.resolve_url(prompt.input().trim()) {
+                    if let Some(url) = Self::check_path(url).await {
+                        info!("🧭 go to {}", url);
+                        if let Err(e) = self.state().navigate(url).await {
+                            self.show_error(e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Complete the path typed in the prompt with the content of its remote parent directory
+    async fn complete_prompt(&mut self) {
+        let input = match &self.prompt {
+            Some(prompt) if matches!(prompt.kind(), PromptKind::GotoPath | PromptKind::Move) => {
+                prompt.input().to_string()
+            }
+            _ => return,
+        };
+        let (parent, _) = split_parent(&input);
+        let parent_url = match self.resolve_url(parent) {
+            Some(parent_url) => parent_url,
+            None => return,
+        };
+        match request::interactive(get_item_list(&parent_url)).await {
+            Ok(items) => {
+                let (completed, names) = complete_path(&input, &items);
+                match names.len() {
+                    0 => info!("🧭 no match in {}", parent_url),
+                    1 => {}
+                    _ => info!("🧭 {}", names.join("  ")),
+                }
+                if let Some(prompt) = self.prompt.as_mut() {
+                    prompt.set_input(completed);
+                }
+            }
+            Err(e) => warn!("🧭 can not complete, failed to list {}: {}", parent_url, e),
+        }
+    }
+
+    /// The url of a typed path: a full url, an absolute path on the current
+    /// proxy, or a path relative to the current directory
+    fn resolve_url(&self, path: &str) -> Option<String> {
+        if path.starts_with("http://") || path.starts_with("https://") {
+            return Some(path.to_string());
+        }
+        let current_dir = self.current_tab().current_dir()?;
+        if path.starts_with('/') {
+            let (origin, _) = split_origin(&current_dir);
+            Some(format!("{}{}", origin, path))
         } else {
-            warn!("No action accociated to {}", key);
-            AppReturn::Continue
+            Some(format!("{}{}", current_dir, path))
+        }
+    }
+
+    /// Check that `url` exists in its parent directory,
+    /// and add the trailing '/' when it is a directory typed without it
+    async fn check_path(url: String) -> Option<String> {
+        let (parent_url, name) = split_parent(&url);
+        if name.is_empty() {
+            return Some(url);
+        }
+        match request::interactive(get_item_list(parent_url)).await {
+            Ok(items) => match items.iter().find(|item| item.name == name) {
+                Some(item) if item.size == -1 => Some(format!("{}/", url)),
+                Some(_) => Some(url),
+                None => {
+                    warn!("🧭 no such file or directory: {}", url);
+                    None
+                }
+            },
+            // The parent may not be listable, let's try anyway
+            Err(_) => Some(url),
+        }
+    }
+
+    /// Show or hide the local pane next to the remote one
+    fn toggle_commander(&mut self) {
+        if self.commander.take().is_none() {
+            let dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+            self.commander = Some(Commander::new(dir));
+        }
+    }
+
+    /// The local pane, when it has the focus
+    fn focused_local(&mut self) -> Option<&mut LocalPane> {
+        match self.commander.as_mut() {
+            Some(commander) if commander.focus == Pane::Local => Some(&mut commander.local),
+            _ => None,
+        }
+    }
+
+    /// Copy the selected file of the focused pane to the other pane, in the IO thread
+    async fn copy(&mut self) {
+        let commander = match &self.commander {
+            Some(commander) => commander,
+            None => return,
+        };
+        let event = match commander.focus {
+            Pane::Local => {
+                let remote_dir = match self.current_tab().current_dir() {
+                    Some(remote_dir) => remote_dir,
+                    None => return,
+                };
+                let local = &commander.local;
+                let ops = local
+                    .targets()
+                    .into_iter()
+                    .filter(|item| {
+                        let is_dir = item.size == -1;
+                        if is_dir {
+                            warn!("📁 only files can be copied, {} is a directory", item.name);
+                        }
+                        !is_dir
+                    })
+                    .map(|item| BatchOp::Upload {
+                        src: local.dir().join(&item.name),
+                        url: format!("{}{}", remote_dir, item.name),
+                    })
+                    .collect::<Vec<_>>();
+                if ops.is_empty() {
+                    return;
+                }
+                if let Some(commander) = self.commander.as_mut() {
+                    commander.local.clear_marks();
+                }
+                IoEvent::Batch(ops)
+            }
+            Pane::Remote => {
+                let urls = self.targets();
+                let ops = urls
+                    .into_iter()
+                    .filter(|url| {
+                        let is_dir = url.ends_with('/');
+                        if is_dir {
+                            warn!("📁 only files can be copied, {} is a directory", url);
+                        }
+                        !is_dir
+                    })
+                    .map(|url| {
+                        let (_, name) = split_parent(&url);
+                        let dest = commander.local.dir().join(name);
+                        BatchOp::Download { url, dest }
+                    })
+                    .collect::<Vec<_>>();
+                if ops.is_empty() {
+                    return;
+                }
+                self.state().clear_marks();
+                IoEvent::Batch(ops)
+            }
+        };
+        self.dispatch(event).await;
+    }
+
+    /// The urls an action applies to: the marked entries, or else the selected one
+    fn targets(&self) -> Vec<String> {
+        let state = self.current_tab();
+        let marked = state.marked_urls();
+        if marked.is_empty() {
+            state.selected_url().into_iter().collect()
+        } else {
+            marked
+        }
+    }
+
+    /// Download the marked files, or the selected one, in the download directory
+    async fn save_targets(&mut self) {
+        let ops = self
+            .targets()
+            .into_iter()
+            .filter(|url| !url.ends_with('/'))
+            .map(|url| {
+                let (_, name) = split_parent(&url);
+                let dest = self.download_dir.join(name);
+                BatchOp::Download { url, dest }
+            })
+            .collect::<Vec<_>>();
+        if ops.is_empty() {
+            warn!("🦁️ no file is selected, directories are not saved");
+            return;
+        }
+        self.state().clear_marks();
+        self.dispatch(IoEvent::Batch(ops)).await;
+    }
+
+    /// The IO thread started an operation of a batch
+    pub fn batch_progress(&mut self, done: usize, total: usize) {
+        self.batch = Some((done, total));
+    }
+
+    /// The IO thread finished a batch, the local pane is refreshed,
+    /// the remote directories are refreshed with `listing_loaded`
+    pub fn batch_done(&mut self, failures: Vec<String>) {
+        self.batch = None;
+        if let Some(commander) = self.commander.as_mut() {
+            commander.local.refresh();
+        }
+        self.show_failures(failures);
+    }
+
+    /// Show what failed in the IO thread, one failure per line
+    pub fn show_failures(&mut self, failures: Vec<String>) {
+        if !failures.is_empty() {
+            self.show_error(failures.join("\n"));
+            self.update_actions();
+        }
+    }
+
+    /// The IO thread created the entry at `url`, the tabs browsing its directory show it
+    pub fn entry_created(&mut self, url: &str, size: i64) {
+        for tab in self.tabs.iter_mut() {
+            tab.entry_created(url, size);
+        }
+    }
+
+    /// The IO thread moved the entry at `from` to `to`, the tabs browsing either directory
+    /// are updated
+    pub fn entry_moved(&mut self, from: &str, to: &str) {
+        let removed = self
+            .tabs
+            .iter_mut()
+            .filter_map(|tab| tab.entry_removed(from))
+            .last();
+        let size = match removed {
+            Some(item) => item.size,
+            None if to.ends_with('/') => -1,
+            None => 0,
+        };
+        self.entry_created(to, size);
+    }
+
+    /// The IO thread listed the directory at `url` again, the tabs browsing it are updated
+    pub fn listing_loaded(&mut self, url: &str, items: Vec<Item>) {
+        for tab in self.tabs.iter_mut() {
+            tab.listing_loaded(url, items.clone());
+        }
+    }
+
+    pub fn batch(&self) -> Option<(usize, usize)> {
+        self.batch
+    }
+
+    pub fn commander(&self) -> Option<&Commander> {
+        self.commander.as_ref()
+    }
+
+    /// Open a new tab next to the current one, at the same location
+    fn new_tab(&mut self) {
+        let mut state = self.current_tab().clone();
+        state.hide_usage();
+        self.tab += 1;
+        self.tabs.insert(self.tab, state);
+        info!("🗂️ tab {} opened", self.tab + 1);
+    }
+
+    /// Close the current tab, the last one can not be closed
+    fn close_tab(&mut self) {
+        if self.tabs.len() == 1 {
+            warn!("🗂️ can not close the last tab");
+            return;
+        }
+        self.tabs.remove(self.tab);
+        info!("🗂️ tab {} closed", self.tab + 1);
+        self.tab = self.tab.min(self.tabs.len() - 1);
+    }
+
+    /// Go to the next tab, the first one after the last
+    fn next_tab(&mut self) {
+        self.tab = (self.tab + 1) % self.tabs.len();
+    }
+
+    /// Go to the previous tab, the last one before the first
+    fn previous_tab(&mut self) {
+        self.tab = (self.tab + self.tabs.len() - 1) % self.tabs.len();
+    }
+
+    /// Bookmark the current location
+    fn add_bookmark(&mut self) {
+        if let AppState::Initialized { current_url, .. } = &self.tabs[self.tab] {
+            if self.bookmarks.add(current_url) {
+                info!("🔖 {} bookmarked", current_url);
+                self.save_bookmarks();
+            } else {
+                info!("🔖 {} is already bookmarked", current_url);
+            }
+        }
+    }
+
+    fn save_bookmarks(&self) {
+        let path = config::bookmarks_path();
+        if let Err(e) = self.bookmarks.save(&path) {
+            error!("🔖 failed to save bookmarks to {}: {}", path.display(), e);
         }
     }
 
     /// We could update the app or dispatch event on tick
     pub async fn update_on_tick(&mut self) -> AppReturn {
         // here we just increment a counter
-        self.state.incr_tick();
+        self.state().incr_tick();
         AppReturn::Continue
     }
 
@@ -108,26 +968,251 @@ impl App {
     pub fn actions(&self) -> &Actions {
         &self.actions
     }
+
+    pub fn bookmarks(&self) -> &Bookmarks {
+        &self.bookmarks
+    }
+
+    pub fn connections(&self) -> &[Connection] {
+        &self.connections
+    }
+
+    /// The name of the active connection
+    pub fn connection(&self) -> &str {
+        &self.connection
+    }
+
+    /// The requests are now signed for the connection `name`
+    pub fn connected(&mut self, name: String) {
+        self.connection = name;
+    }
+
+    pub fn popup(&self) -> Option<&Popup> {
+        self.popup.as_ref()
+    }
+
+    pub fn prompt(&self) -> Option<&Prompt> {
+        self.prompt.as_ref()
+    }
+
+    /// The state of the displayed tab
     pub fn state(&mut self) -> &mut AppState {
-        &mut self.state
+        &mut self.tabs[self.tab]
+    }
+
+    fn current_tab(&self) -> &AppState {
+        &self.tabs[self.tab]
+    }
+
+    pub fn tabs(&self) -> &[AppState] {
+        &self.tabs
+    }
+
+    pub fn tab_index(&self) -> usize {
+        self.tab
     }
 
     pub fn is_loading(&self) -> bool {
         self.is_loading
     }
 
-    pub async fn initialized(&mut self, init_url: String) {
+    /// Browse the loaded base url, the previous tabs are kept when it failed to load
+    pub fn initialized(&mut self, state: Result<AppState, RequestError>) {
+        match state {
+            Ok(state) => {
+                self.tabs = vec![state];
+                self.tab = 0;
+            }
+            Err(e) => self.show_error(e),
+        }
         // Update contextual actions
-        self.actions = vec![
+        self.update_actions();
+    }
+
+    /// Show why a transition failed in a popup, over the previous view
+    fn show_error(&mut self, e: impl fmt::Display) {
+        error!("☹️ {}", e);
+        self.popup = Some(Popup::Error(e.to_string()));
+    }
+
+    /// The contextual actions depend on what is displayed
+    fn update_actions(&mut self) {
+        self.actions = if let Some(Popup::Error(_)) = &self.popup {
+            vec![Action::Close].into()
+        } else if !self.current_tab().is_initialized() {
+            vec![Action::Quit].into()
+        } else if let Some(popup) = &self.popup {
+            match popup {
+                Popup::Error(_) => vec![Action::Close].into(),
+                Popup::Bookmarks { .. } => Self::bookmarks_actions(),
+                Popup::Connections { .. } => Self::connections_actions(),
+                Popup::ConfirmDelete { .. } => Self::confirm_delete_actions(),
+                Popup::Properties(properties) if properties.preview().is_some() => {
+                    vec![Action::Confirm, Action::Cancel].into()
+                }
+                Popup::Properties(_) => Self::properties_actions(),
+            }
+        } else if self.current_tab().is_usage_shown() {
+            Self::usage_actions()
+        } else if self.commander.is_some() {
+            Self::commander_actions()
+        } else {
+            Self::browse_actions()
+        };
+    }
+
+    /// Actions available when browsing the remote files
+    fn browse_actions() -> Actions {
+        vec![
             Action::Quit,
             Action::BackToPreviours,
             Action::StepInto,
             Action::MoveUp,
             Action::MoveDown,
             Action::Save,
+            Action::HistoryBack,
+            Action::HistoryForward,
+            Action::DiskUsage,
+            Action::AddBookmark,
+            Action::ShowBookmarks,
+            Action::GotoPath,
+            Action::NewTab,
+            Action::CloseTab,
+            Action::NextTab,
+            Action::PreviousTab,
+            Action::Commander,
+            Action::ToggleMark,
+            Action::MarkAll,
+            Action::InvertMarks,
+            Action::MarkGlob,
+            Action::Delete,
+            Action::Mkdir,
+            Action::Rename,
+            Action::Move,
+            Action::Properties,
+            Action::Edit,
+            Action::OpenWith,
+            Action::Connections,
+        ]
+        .into()
+    }
+
+    /// Actions available in the commander layout
+    fn commander_actions() -> Actions {
+        vec![
+            Action::Quit,
+            Action::BackToPreviours,
+            Action::StepInto,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::SwitchPane,
+            Action::Copy,
+            Action::Commander,
+            Action::ToggleMark,
+            Action::MarkAll,
+            Action::InvertMarks,
+            Action::MarkGlob,
+        ]
+        .into()
+    }
+
+    /// Actions available in the properties dialog, while editing
+    fn properties_actions() -> Actions {
+        vec![
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::EditProperty,
+            Action::PreviewChanges,
+            Action::Close,
+        ]
+        .into()
+    }
+
+    /// Actions available in the delete confirmation dialog
+    fn confirm_delete_actions() -> Actions {
+        vec![
+            Action::Confirm,
+            Action::MoveToTrash,
+            Action::ToggleRecursive,
+            Action::Cancel,
+        ]
+        .into()
+    }
+
+    /// Actions available in the bookmarks popup
+    fn bookmarks_actions() -> Actions {
+        vec![
+            Action::Close,
+            Action::StepInto,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::MoveBookmarkUp,
+            Action::MoveBookmarkDown,
+            Action::RenameBookmark,
+            Action::DeleteBookmark,
+        ]
+        .into()
+    }
+
+    /// Actions available in the connection picker
+    fn connections_actions() -> Actions {
+        vec![
+            Action::Close,
+            Action::StepInto,
+            Action::MoveUp,
+            Action::MoveDown,
+        ]
+        .into()
+    }
+
+    /// Actions available in the disk usage explorer
+    fn usage_actions() -> Actions {
+        vec![
+            Action::Quit,
+            Action::BackToPreviours,
+            Action::StepInto,
+            Action::MoveUp,
+            Action::MoveDown,
+            Action::Rescan,
+            Action::DiskUsage,
         ]
-        .into();
-        self.state = AppState::initialized(init_url).await;
+        .into()
+    }
+
+    /// Enter or leave the disk usage explorer, entering it starts a background scan
+    async fn toggle_usage(&mut self) {
+        if self.state().is_usage_shown() {
+            self.state().hide_usage();
+        } else if let Some(root_url) = self.state().current_dir() {
+            self.state().show_usage(root_url.clone());
+            self.dispatch(IoEvent::ScanUsage(root_url)).await;
+        }
+    }
+
+    /// A background scan is finished
+    pub fn usage_scanned(&mut self, root_url: &str, root: UsageNode) {
+        let tab = self.tabs.iter_mut().find(|tab| {
+            tab.usage()
+                .map(|usage| usage.root_url() == root_url)
+                .unwrap_or(false)
+        });
+        match tab {
+            Some(tab) => tab.usage_scanned(root_url, root),
+            None => warn!("📊 scan of {} is no longer displayed", root_url),
+        }
+    }
+
+    /// A background scan failed, its error is shown in the explorer
+    pub fn usage_scan_failed(&mut self, root_url: &str, error: String) {
+        let tab = self.tabs.iter_mut().find(|tab| {
+            tab.usage()
+                .map(|usage| usage.root_url() == root_url)
+                .unwrap_or(false)
+        });
+        match tab {
+            Some(tab) => tab.usage_scan_failed(root_url, error),
+            None => warn!("📊 scan of {} is no longer displayed", root_url),
+        }
     }
 
     pub fn loaded(&mut self) {
@@ -135,6 +1220,152 @@ impl App {
     }
 
     pub fn slept(&mut self) {
-        self.state.incr_sleep();
+        self.state().incr_sleep();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+
+    fn app(tabs: Vec<AppState>) -> App {
+        let (io_tx, _) = tokio::sync::mpsc::channel(16);
+        let settings = Config::default().settings(None).unwrap();
+        let mut app = App::new(io_tx, &settings);
+        app.tabs = tabs;
+        app
+    }
+
+    fn item(name: &str, size: i64) -> Item {
+        Item {
+            name: name.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn should_show_every_failure() {
+        let url = "http://localhost:7878/user/alice/";
+        let mut app = app(vec![AppState::browsing(url, vec![item("a.csv", 12)])]);
+        app.show_failures(Vec::new());
+        assert!(app.popup().is_none());
+
+        app.show_failures(vec![
+            "failed to delete a.csv".to_string(),
+            "failed to delete b.csv".to_string(),
+        ]);
+        match app.popup() {
+            Some(Popup::Error(message)) => {
+                assert_eq!(message, "failed to delete a.csv\nfailed to delete b.csv")
+            }
+            _ => panic!("no error popup"),
+        }
+        assert_eq!(app.actions().actions(), &[Action::Close]);
+    }
+
+    fn tab_urls(app: &App) -> Vec<&str> {
+        app.tabs
+            .iter()
+            .map(|tab| match tab {
+                AppState::Initialized { current_url, .. } => current_url.as_str(),
+                _ => "",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn should_keep_the_last_tab() {
+        let url = "http://localhost:7878/user/alice/";
+        let mut app = app(vec![AppState::browsing(url, Vec::new())]);
+        app.close_tab();
+        assert_eq!(tab_urls(&app), vec![url]);
+        assert_eq!(app.tab, 0);
+    }
+
+    #[test]
+    fn should_clamp_the_tab_after_closing() {
+        let urls = [
+            "http://localhost:7878/a/",
+            "http://localhost:7878/b/",
+            "http://localhost:7878/c/",
+        ];
+        let mut app = app(urls
+            .iter()
+            .map(|url| AppState::browsing(url, Vec::new()))
+            .collect());
+        app.tab = 1;
+        app.close_tab();
+        assert_eq!(tab_urls(&app), vec![urls[0], urls[2]]);
+        assert_eq!(app.tab, 1);
+
+        app.close_tab();
+        assert_eq!(tab_urls(&app), vec![urls[0]]);
+        assert_eq!(app.tab, 0);
+    }
+
+    #[test]
+    fn should_wrap_around_the_tabs() {
+        let urls = [
+            "http://localhost:7878/a/",
+            "http://localhost:7878/b/",
+            "http://localhost:7878/c/",
+        ];
+        let mut app = app(urls
+            .iter()
+            .map(|url| AppState::browsing(url, Vec::new()))
+            .collect());
+        app.previous_tab();
+        assert_eq!(app.tab, 2);
+        app.next_tab();
+        assert_eq!(app.tab, 0);
+        app.next_tab();
+        app.next_tab();
+        assert_eq!(app.tab, 2);
+        app.previous_tab();
+        assert_eq!(app.tab, 1);
+    }
+
+    #[tokio::test]
+    async fn should_keep_the_selection_of_each_pane() {
+        let dir = std::env::temp_dir().join(format!("hdfs-proxy-tui-panes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        let url = "http://localhost:7878/user/alice/";
+        let items = vec![item("x.csv", 12), item("y.csv", 12), item("z.csv", 12)];
+        let mut app = app(vec![AppState::browsing(url, items)]);
+        app.commander = Some(Commander::new(dir.clone()));
+        let selection = |app: &App| {
+            let commander = app.commander().unwrap();
+            (
+                commander.focus,
+                commander.local.selected().unwrap().name.clone(),
+                app.current_tab().selected_item().unwrap().name.clone(),
+            )
+        };
+
+        app.do_browse_action(Action::MoveDown).await;
+        app.do_browse_action(Action::MoveDown).await;
+        assert_eq!(
+            selection(&app),
+            (Pane::Local, "c.txt".to_string(), "x.csv".to_string())
+        );
+
+        app.do_browse_action(Action::SwitchPane).await;
+        app.do_browse_action(Action::MoveDown).await;
+        assert_eq!(
+            selection(&app),
+            (Pane::Remote, "c.txt".to_string(), "y.csv".to_string())
+        );
+
+        app.do_browse_action(Action::SwitchPane).await;
+        app.do_browse_action(Action::MoveUp).await;
+        assert_eq!(
+            selection(&app),
+            (Pane::Local, "b.txt".to_string(), "y.csv".to_string())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,16 @@
+use crate::app::properties::Properties;
+
+/// A popup drawn over the body, it gets the contextual actions until it is closed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Popup {
+    /// The bookmark list, with the selected bookmark
+    Bookmarks { index: usize },
+    /// The connection picker, with the selected connection
+    Connections { index: usize },
+    /// Ask before deleting the urls, directories end with a '/'
+    ConfirmDelete { urls: Vec<String>, recursive: bool },
+    /// The properties dialog of an entry
+    Properties(Properties),
+    /// Why the last transition failed, over the previous view
+    Error(String),
+}
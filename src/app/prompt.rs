@@ -0,0 +1,202 @@
+use crate::app::properties::FIELDS;
+use crate::app::state::Item;
+
+/// What the user is typing for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PromptKind {
+    /// The new name of the bookmark at this index
+    RenameBookmark(usize),
+    /// A path (or a full url) to go to
+    GotoPath,
+    /// A glob pattern of the entries to mark
+    MarkGlob,
+    /// The path of the directory to create
+    Mkdir,
+    /// The new name of the selected entry
+    Rename,
+    /// The directory where the selected (or marked) entries go
+    Move,
+    /// The new value of a field of the properties dialog
+    EditProperty(usize),
+}
+
+impl PromptKind {
+    pub fn title(&self) -> &'static str {
+        match self {
+            PromptKind::RenameBookmark(_) => "Bookmark name",
+            PromptKind::GotoPath => "Go to path, <Tab> to complete",
+            PromptKind::MarkGlob => "Mark entries matching",
+            PromptKind::Mkdir => "New directory",
+            PromptKind::Rename => "Rename to",
+            PromptKind::Move => "Move to directory, <Tab> to complete",
+            PromptKind::EditProperty(index) => FIELDS[*index],
+        }
+    }
+}
+
+/// A single line text input, drawn over the body
+#[derive(Clone, Debug)]
+pub struct Prompt {
+    kind: PromptKind,
+    input: String,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, input: &str) -> Self {
+        Self {
+            kind,
+            input: input.to_string(),
+        }
+    }
+
+    pub fn kind(&self) -> PromptKind {
+        self.kind
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn push(&mut self, c: char) {
+        self.input.push(c);
+    }
+
+    pub fn pop(&mut self) {
+        self.input.pop();
+    }
+
+    pub fn set_input(&mut self, input: String) {
+        self.input = input;
+    }
+}
+
+/// Split a path into its parent directory (ending with a '/') and the last partial name
+pub fn split_parent(path: &str) -> (&str, &str) {
+    match path.rfind('/') {
+        Some(index) => path.split_at(index + 1),
+        None => ("", path),
+    }
+}
+
+/// The url of the entry at `from` renamed to `name`, in the same directory
+pub fn renamed_url(from: &str, name: &str) -> String {
+    let (parent, _) = split_parent(from.trim_end_matches('/'));
+    let mut to = format!("{}{}", parent, name);
+    if from.ends_with('/') {
+        to.push('/');
+    }
+    to
+}
+
+/// The url of the entry at `from` moved to the directory at `dir_url`, ending with a '/'
+pub fn moved_url(from: &str, dir_url: &str) -> String {
+    let (_, name) = split_parent(from.trim_end_matches('/'));
+    let mut to = format!("{}{}", dir_url, name);
+    if from.ends_with('/') {
+        to.push('/');
+    }
+    to
+}
+
+/// Complete the last segment of `path` with the `items` of its parent directory.
+///
+/// A single candidate is fully completed (with a trailing '/' for directories),
+/// several candidates are completed up to their common prefix.
+/// Returns the matching names along with the completed path.
+pub fn complete_path(path: &str, items: &[Item]) -> (String, Vec<String>) {
+    let (parent, partial) = split_parent(path);
+    let candidates = items
+        .iter()
+        .filter(|item| item.name.starts_with(partial))
+        .collect::<Vec<_>>();
+    let names = candidates
+        .iter()
+        .map(|item| item.name.clone())
+        .collect::<Vec<_>>();
+    let completed = match candidates.as_slice() {
+        [] => path.to_string(),
+        [item] if item.size == -1 => format!("{}{}/", parent, item.name),
+        [item] => format!("{}{}", parent, item.name),
+        [first, others @ ..] => {
+            let common = others.iter().fold(first.name.as_str(), |common, item| {
+                let len = common
+                    .char_indices()
+                    .zip(item.name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map(|((index, c), _)| index + c.len_utf8())
+                    .unwrap_or(0);
+                &common[..len]
+            });
+            format!("{}{}", parent, common)
+        }
+    };
+    (completed, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn items() -> Vec<Item> {
+        vec![
+            Item {
+                name: "warehouse".to_string(),
+                size: -1,
+            },
+            Item {
+                name: "wal.log".to_string(),
+                size: 12,
+            },
+            Item {
+                name: "tmp".to_string(),
+                size: -1,
+            },
+        ]
+    }
+
+    #[test]
+    fn should_complete_single_directory() {
+        let (completed, names) = complete_path("/user/t", &items());
+        assert_eq!(completed, "/user/tmp/");
+        assert_eq!(names, vec!["tmp"]);
+    }
+
+    #[test]
+    fn should_complete_common_prefix() {
+        let (completed, names) = complete_path("/user/w", &items());
+        assert_eq!(completed, "/user/wa");
+        assert_eq!(names.len(), 2);
+        let (completed, _) = complete_path("/user/wal", &items());
+        assert_eq!(completed, "/user/wal.log");
+    }
+
+    #[test]
+    fn should_keep_path_without_candidate() {
+        let (completed, names) = complete_path("/user/x", &items());
+        assert_eq!(completed, "/user/x");
+        assert!(names.is_empty());
+    }
+
+    #[test]
+    fn should_build_rename_and_move_targets() {
+        let url = "http://localhost:7878/user/alice/";
+        assert_eq!(
+            renamed_url(&format!("{}a.csv", url), "b.csv"),
+            format!("{}b.csv", url)
+        );
+        assert_eq!(
+            renamed_url(&format!("{}logs/", url), "old"),
+            format!("{}old/", url)
+        );
+        let dir_url = "http://localhost:7878/tmp/";
+        assert_eq!(
+            moved_url(&format!("{}a.csv", url), dir_url),
+            "http://localhost:7878/tmp/a.csv"
+        );
+        assert_eq!(
+            moved_url(&format!("{}logs/", url), dir_url),
+            "http://localhost:7878/tmp/logs/"
+        );
+    }
+}
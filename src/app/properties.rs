@@ -0,0 +1,319 @@
+use std::fmt::{self, Display};
+
+use serde::Deserialize;
+
+/// The names of the editable fields, in display order
+pub const FIELDS: [&str; 4] = ["Permission", "Owner", "Group", "Replication"];
+const PERMISSION: usize = 0;
+const OWNER: usize = 1;
+const GROUP: usize = 2;
+const REPLICATION: usize = 3;
+
+/// The HDFS highest replication factor (`dfs.replication.max` default)
+const MAX_REPLICATION: u16 = 512;
+
+/// The HDFS properties of a remote entry, as returned by the proxy
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct FileStatus {
+    /// Octal permission bits, like "755"
+    pub permission: String,
+    pub owner: String,
+    pub group: String,
+    /// Directories have no replication factor
+    #[serde(default)]
+    pub replication: u16,
+}
+
+/// A change to send, the equivalent of a `hdfs dfs` command
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PropertyChange {
+    Permission(String),
+    Owner { owner: String, group: String },
+    Replication(u16),
+}
+
+impl PropertyChange {
+    /// The query string parameters of the request
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        match self {
+            PropertyChange::Permission(permission) => {
+                vec![
+                    ("op", "SETPERMISSION".into()),
+                    ("permission", permission.clone()),
+                ]
+            }
+            PropertyChange::Owner { owner, group } => vec![
+                ("op", "SETOWNER".into()),
+                ("owner", owner.clone()),
+                ("group", group.clone()),
+            ],
+            PropertyChange::Replication(replication) => vec![
+                ("op", "SETREPLICATION".into()),
+                ("replication", replication.to_string()),
+            ],
+        }
+    }
+}
+
+impl Display for PropertyChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PropertyChange::Permission(permission) => write!(f, "chmod {}", permission),
+            PropertyChange::Owner { owner, group } => write!(f, "chown {}:{}", owner, group),
+            PropertyChange::Replication(replication) => write!(f, "setrep {}", replication),
+        }
+    }
+}
+
+/// The properties dialog of an entry: the loaded status and the edited values
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Properties {
+    url: String,
+    status: FileStatus,
+    /// Edited values, in the `FIELDS` order
+    values: Vec<String>,
+    index: usize,
+    /// The changes to send, once validated
+    preview: Option<Vec<PropertyChange>>,
+    error: Option<String>,
+}
+
+impl Properties {
+    pub fn new(url: String, status: FileStatus) -> Self {
+        let values = vec![
+            status.permission.clone(),
+            status.owner.clone(),
+            status.group.clone(),
+            status.replication.to_string(),
+        ];
+        Self {
+            url,
+            status,
+            values,
+            index: 0,
+            preview: None,
+            error: None,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.url.ends_with('/')
+    }
+
+    pub fn status(&self) -> &FileStatus {
+        &self.status
+    }
+
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn preview(&self) -> Option<&[PropertyChange]> {
+        self.preview.as_deref()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    pub fn move_up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.index = (self.index + 1).min(self.editable_fields() - 1);
+    }
+
+    pub fn set_value(&mut self, index: usize, value: &str) {
+        if let Some(old) = self.values.get_mut(index) {
+            *old = value.trim().to_string();
+            self.error = None;
+        }
+    }
+
+    /// Validate the edited values and show the changes that would be sent,
+    /// returns false when a value is invalid or nothing changed
+    pub fn dry_run(&mut self) -> bool {
+        match self.changes() {
+            Ok(changes) if changes.is_empty() => {
+                self.error = Some("nothing changed".to_string());
+                false
+            }
+            Ok(changes) => {
+                self.preview = Some(changes);
+                true
+            }
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+
+    /// Go back to editing from the preview
+    pub fn cancel_preview(&mut self) {
+        self.preview = None;
+    }
+
+    /// The replication factor of directories can not be edited
+    fn editable_fields(&self) -> usize {
+        if self.is_dir() {
+            REPLICATION
+        } else {
+            FIELDS.len()
+        }
+    }
+
+    /// The validated changes between the loaded status and the edited values
+    fn changes(&self) -> Result<Vec<PropertyChange>, String> {
+        let mut changes = Vec::new();
+        let permission = &self.values[PERMISSION];
+        validate_permission(permission)?;
+        if permission.trim_start_matches('0') != self.status.permission.trim_start_matches('0') {
+            changes.push(PropertyChange::Permission(permission.clone()));
+        }
+
+        let (owner, group) = (&self.values[OWNER], &self.values[GROUP]);
+        validate_name("owner", owner)?;
+        validate_name("group", group)?;
+        if owner != &self.status.owner || group != &self.status.group {
+            changes.push(PropertyChange::Owner {
+                owner: owner.clone(),
+                group: group.clone(),
+            });
+        }
+
+        if !self.is_dir() {
+            let replication = validate_replication(&self.values[REPLICATION])?;
+            if replication != self.status.replication {
+                changes.push(PropertyChange::Replication(replication));
+            }
+        }
+        Ok(changes)
+    }
+}
+
+/// Three or four octal digits, the sticky bit being the only special bit HDFS knows
+fn validate_permission(permission: &str) -> Result<(), String> {
+    let valid = (3..=4).contains(&permission.len())
+        && permission.chars().all(|c| ('0'..='7').contains(&c))
+        && u16::from_str_radix(permission, 8).is_ok_and(|bits| bits <= 0o1777);
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid permission {:?}, expected octal like 755",
+            permission
+        ))
+    }
+}
+
+fn validate_name(field: &str, name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err(format!("the {} can not be empty", field))
+    } else if name
+        .chars()
+        .any(|c| c.is_whitespace() || c == ':' || c == '/')
+    {
+        Err(format!("invalid {} {:?}", field, name))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_replication(replication: &str) -> Result<u16, String> {
+    match replication.parse::<u16>() {
+        Ok(replication) if (1..=MAX_REPLICATION).contains(&replication) => Ok(replication),
+        _ => Err(format!(
+            "invalid replication {:?}, expected 1 to {}",
+            replication, MAX_REPLICATION
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn properties(url: &str) -> Properties {
+        let status = FileStatus {
+            permission: "644".to_string(),
+            owner: "hive".to_string(),
+            group: "hadoop".to_string(),
+            replication: 3,
+        };
+        Properties::new(url.to_string(), status)
+    }
+
+    #[test]
+    fn should_validate_values() {
+        assert!(validate_permission("755").is_ok());
+        assert!(validate_permission("1777").is_ok());
+        assert!(validate_permission("788").is_err());
+        assert!(validate_permission("2755").is_err());
+        assert!(validate_permission("rwx").is_err());
+        assert!(validate_name("owner", "alice").is_ok());
+        assert!(validate_name("owner", "al ice").is_err());
+        assert_eq!(validate_replication("2"), Ok(2));
+        assert!(validate_replication("0").is_err());
+    }
+
+    #[test]
+    fn should_preview_only_the_changes() {
+        let mut properties = properties("http://host/data/file.csv");
+        assert!(!properties.dry_run());
+        assert_eq!(properties.error(), Some("nothing changed"));
+
+        properties.set_value(PERMISSION, "0640");
+        properties.set_value(GROUP, "analysts");
+        assert!(properties.dry_run());
+        let preview = properties
+            .preview()
+            .unwrap()
+            .iter()
+            .map(|change| change.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(preview, vec!["chmod 0640", "chown hive:analysts"]);
+    }
+
+    #[test]
+    fn should_reject_invalid_values() {
+        let mut properties = properties("http://host/data/file.csv");
+        properties.set_value(REPLICATION, "1000");
+        assert!(!properties.dry_run());
+        assert!(properties.preview().is_none());
+        assert!(properties.error().unwrap().contains("replication"));
+    }
+
+    #[test]
+    fn should_ignore_replication_of_directories() {
+        let mut properties = properties("http://host/data/");
+        properties.set_value(REPLICATION, "abc");
+        properties.set_value(OWNER, "alice");
+        assert!(properties.dry_run());
+        assert_eq!(properties.preview().unwrap().len(), 1);
+        for _ in 0..5 {
+            properties.move_down();
+        }
+        assert_eq!(properties.index(), GROUP);
+    }
+
+    #[test]
+    fn should_build_query() {
+        assert_eq!(
+            PropertyChange::Replication(2).query(),
+            vec![
+                ("op", "SETREPLICATION".to_string()),
+                ("replication", "2".to_string())
+            ]
+        );
+    }
+}
@@ -1,11 +1,16 @@
-use crate::request::dump_file;
+use crate::app::history::{Cursor, History};
+use crate::app::usage::{DiskUsage, UsageNode};
+use crate::request;
 use crate::request::get_file_chunk;
 use crate::request::get_item_list;
-use log::error;
+use crate::request::split_origin;
+use crate::request::RequestError;
+use glob::Pattern;
 use log::info;
 use log::warn;
 use serde::Deserialize;
 use serde::Serialize;
+use std::collections::HashSet;
 use std::time::Duration;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -34,69 +39,34 @@ pub enum AppState {
         frame_start: usize,
         frame_end: usize,
         file_chunk: Option<String>,
+        usage: Option<Box<DiskUsage>>,
+        history: Box<History>,
+        /// Names of the marked entries of the current directory
+        marked: HashSet<String>,
     },
 }
 
 impl AppState {
-    pub async fn initialized(url: String) -> Self {
-        let duration = Duration::from_secs(1);
-        let counter_sleep = 0;
-        let counter_tick = 0;
-        if url.ends_with('/') {
-            let current_items = get_item_list(&url).await;
-            match current_items {
-                Ok(items) => {
-                    let current_index = 0;
-                    let last_index = 0;
-                    AppState::Initialized {
-                        duration,
-                        counter_sleep,
-                        counter_tick,
-                        current_url: url,
-                        current_index,
-                        last_index,
-                        current_items: Some(items),
-                        show_file: false,
-                        frame_start: 0,
-                        frame_end: 0, // the frame info should not be placed here
-                        file_chunk: None,
-                    }
-                }
-                Err(e) => {
-                    error!(
-                        "☹️ failed to init: can not get the item list from url: {}",
-                        e
-                    );
-                    AppState::Init
-                }
-            }
-        } else {
-            let file_chunk = get_file_chunk(&url).await;
-            match file_chunk {
-                Ok(chunk) => {
-                    AppState::Initialized {
-                        duration,
-                        counter_sleep,
-                        counter_tick,
-                        current_url: url,
-                        current_index: 0,
-                        last_index: 0,
-                        current_items: None,
-                        show_file: true,
-                        frame_start: 0,
-                        frame_end: 0, // the frame info should not be placed here
-                        file_chunk: Some(chunk),
-                    }
-                }
-                Err(e) => {
-                    error!(
-                        "☹️ failed to init: can not get the file chunk from url: {}",
-                        e
-                    );
-                    AppState::Init
-                }
-            }
-        }
+    /// The state browsing `url`, the error tells why it can not be loaded
+    pub async fn initialized(url: String) -> Result<Self, RequestError> {
+        let mut state = AppState::Initialized {
+            duration: Duration::from_secs(1),
+            counter_sleep: 0,
+            counter_tick: 0,
+            current_url: url.clone(),
+            current_index: 0,
+            last_index: 0,
+            current_items: None,
+            show_file: false,
+            frame_start: 0,
+            frame_end: 0, // the frame info should not be placed here
+            file_chunk: None,
+            usage: None,
+            history: Box::default(),
+            marked: HashSet::new(),
+        };
+        state.load(url).await?;
+        Ok(state)
     }
 
     pub fn is_initialized(&self) -> bool {
@@ -205,142 +175,223 @@ impl AppState {
         }
     }
 
-    pub async fn update_state(&mut self, content_type: ContentState) {
-        if let Self::Initialized { current_url, .. } = self {
-            match content_type {
-                ContentState::ItemList => {
-                    let current_items = get_item_list(&current_url).await;
-                    let new_state = match current_items {
-                        Ok(items) => {
-                            let current_index = 0;
-                            let last_index = 0;
-                            AppState::Initialized {
-                                current_url: current_url.clone(),
-                                current_index,
-                                last_index,
-                                current_items: Some(items),
-                                show_file: false,
-                                frame_start: 0,
-                                frame_end: 0, // the frame info should not be placed here
-                                file_chunk: None,
-                                duration: Duration::from_secs(1),
-                                counter_sleep: 0,
-                                counter_tick: self.count_tick().unwrap_or(0),
-                            }
-                        }
-                        Err(e) => {
-                            panic!(
-                                "☹️ failed to init: can not get the item list from url: {}",
-                                e
-                            );
-                        }
-                    };
-                    *self = new_state;
-                }
-                ContentState::FileChunk => {
-                    let file_chunk = get_file_chunk(&current_url).await;
-                    let new_state = match file_chunk {
-                        Ok(chunk) => {
-                            AppState::Initialized {
-                                current_url: current_url.clone(),
-                                current_index: 0,
-                                last_index: 0,
-                                current_items: None,
-                                show_file: true,
-                                frame_start: 0,
-                                frame_end: 0, // the frame info should not be placed here
-                                file_chunk: Some(chunk),
-                                duration: Duration::from_secs(1),
-                                counter_sleep: 0,
-                                counter_tick: self.count_tick().unwrap_or(0),
-                            }
-                        }
-                        Err(e) => {
-                            panic!(
-                                "☹️ failed to init: can not get the file chunk from url: {}",
-                                e
-                            );
-                        }
-                    };
-                    *self = new_state;
-                }
+    /// Browse `url`: the entries of a directory (ending with a '/') or the chunk of a file.
+    /// The state is only changed once it is loaded, a failure keeps the previous view
+    async fn load(&mut self, url: String) -> Result<(), RequestError> {
+        if let Self::Initialized {
+            duration,
+            counter_sleep,
+            current_url,
+            current_index,
+            last_index,
+            current_items,
+            show_file,
+            frame_start,
+            frame_end,
+            file_chunk,
+            usage,
+            marked,
+            ..
+        } = self
+        {
+            if url.ends_with('/') {
+                *current_items = Some(request::interactive(get_item_list(&url)).await?);
+                *file_chunk = None;
+                *show_file = false;
+            } else {
+                *file_chunk = Some(request::interactive(get_file_chunk(&url)).await?);
+                *current_items = None;
+                *show_file = true;
             }
+            *current_url = url;
+            *current_index = 0;
+            *last_index = 0;
+            *frame_start = 0;
+            *frame_end = 0; // the frame info should not be placed here
+            *usage = None;
+            marked.clear();
+            *duration = Duration::from_secs(1);
+            *counter_sleep = 0;
         }
+        Ok(())
     }
 
-    pub async fn back_to_previours(&mut self) {
+    pub async fn back_to_previours(&mut self) -> Result<(), RequestError> {
         info!("👈 back to previours");
-        let content_state = self.get_state();
-        if let Self::Initialized { current_url, .. } = self {
-            match content_state {
-                ContentState::FileChunk => {
-                    let last_slash_index =
-                        current_url.rfind('/').expect("can not find the last slash");
-                    *current_url = current_url[..=last_slash_index].to_string();
+        let current_url = match self.current_url() {
+            Some(current_url) => current_url,
+            None => return Ok(()),
+        };
+        // The root of the proxy has no parent
+        if split_origin(current_url).1 == "/" {
+            return Ok(());
+        }
+        let (parent, came_from) = match split_entry_url(current_url) {
+            (parent, came_from) if !parent.is_empty() => {
+                (parent.to_string(), came_from.to_string())
+            }
+            _ => return Ok(()),
+        };
+        self.visit(parent, Some(&came_from)).await
+    }
+
+    /// Go back to the previously visited location
+    pub async fn history_back(&mut self) -> Result<(), RequestError> {
+        info!("⏪ history back");
+        self.remember_cursor();
+        let current_url = self.current_url().map(str::to_string);
+        match current_url.and_then(|current_url| self.history_mut()?.back(current_url)) {
+            Some(url) => {
+                let result = self.go_to(url.clone()).await;
+                // Failed, the location we left is the current one again
+                if let (Err(_), Some(history)) = (&result, self.history_mut()) {
+                    history.forward(url);
                 }
-                ContentState::ItemList => {
-                    let remove_last_slash = current_url.trim_end_matches('/');
-                    let last_slash_index = remove_last_slash
-                        .rfind('/')
-                        .expect("can not find the last slash");
-                    *current_url = current_url[..=last_slash_index].to_string();
+                result
+            }
+            None => {
+                info!("no previous location");
+                Ok(())
+            }
+        }
+    }
+
+    /// Go forward to the location we came back from
+    pub async fn history_forward(&mut self) -> Result<(), RequestError> {
+        info!("⏩ history forward");
+        self.remember_cursor();
+        let current_url = self.current_url().map(str::to_string);
+        match current_url.and_then(|current_url| self.history_mut()?.forward(current_url)) {
+            Some(url) => {
+                let result = self.go_to(url.clone()).await;
+                // Failed, the location we left is the current one again
+                if let (Err(_), Some(history)) = (&result, self.history_mut()) {
+                    history.back(url);
                 }
+                result
+            }
+            None => {
+                info!("no next location");
+                Ok(())
             }
-            self.update_state(self.get_state()).await;
         }
     }
 
-    pub async fn step_into(&mut self) {
-        info!("👉 step into");
+    /// Open `url` and record the current location in the history
+    pub async fn navigate(&mut self, url: String) -> Result<(), RequestError> {
+        self.visit(url, None).await
+    }
+
+    /// Open `url` then record the location we left in the history,
+    /// `came_from` is the entry selected when we never were in this directory
+    async fn visit(&mut self, url: String, came_from: Option<&str>) -> Result<(), RequestError> {
+        self.remember_cursor();
+        let left = match self.current_url() {
+            Some(left) => left.to_string(),
+            None => return Ok(()),
+        };
+        self.load(url).await?;
+        if let Some(history) = self.history_mut() {
+            history.visit(left);
+        }
+        self.restore_cursor(came_from);
+        Ok(())
+    }
+
+    async fn go_to(&mut self, url: String) -> Result<(), RequestError> {
+        self.load(url).await?;
+        self.restore_cursor(None);
+        Ok(())
+    }
+
+    fn current_url(&self) -> Option<&str> {
+        if let Self::Initialized { current_url, .. } = self {
+            Some(current_url)
+        } else {
+            None
+        }
+    }
+
+    fn history_mut(&mut self) -> Option<&mut History> {
+        if let Self::Initialized { history, .. } = self {
+            Some(history)
+        } else {
+            None
+        }
+    }
+
+    /// Remember the cursor of the current directory, to restore it when we come back
+    fn remember_cursor(&mut self) {
         if let Self::Initialized {
             current_url,
             current_index,
-            current_items,
-            show_file,
-            file_chunk,
+            frame_start,
+            frame_end,
+            history,
             ..
         } = self
         {
-            let item = &current_items.as_ref().unwrap()[*current_index as usize];
-            current_url.push_str(&item.name);
-            if item.size == -1 {
-                current_url.push('/');
-                let new_current_items = get_item_list(&current_url).await;
-                match new_current_items {
-                    Ok(items) => {
-                        *current_items = Some(items);
-                        *current_index = 0;
-                        *show_file = false;
-                    }
-                    Err(e) => {
-                        error!(
-                            "☹️ failed to step into: can not get the item list from url: {}",
-                            e
-                        );
-                    }
-                }
-            } else {
-                let cur_file_chunk = get_file_chunk(&current_url).await;
-                match cur_file_chunk {
-                    Ok(chunk) => {
-                        *current_items = None;
-                        info!("👉 file chunk: {}", &chunk);
-                        *file_chunk = Some(chunk);
-                    }
-                    Err(e) => {
-                        error!(
-                            "☹️ failed to step into: can not get the file chunk from url: {}",
-                            e
-                        );
-                    }
-                }
-                *show_file = true;
+            if current_url.ends_with('/') {
+                let cursor = Cursor {
+                    index: *current_index,
+                    frame_start: *frame_start,
+                    frame_end: *frame_end,
+                };
+                history.remember(current_url, cursor);
+            }
+        }
+    }
+
+    /// Restore the remembered cursor of the current directory,
+    /// or select the `came_from` entry if we never were here
+    fn restore_cursor(&mut self, came_from: Option<&str>) {
+        if let Self::Initialized {
+            current_url,
+            current_index,
+            current_items: Some(items),
+            frame_start,
+            frame_end,
+            history,
+            ..
+        } = self
+        {
+            // The directory may have changed since we left it
+            let cursor = history
+                .cursor(current_url)
+                .filter(|cursor| (cursor.index as usize) < items.len())
+                .filter(|cursor| cursor.frame_end <= items.len());
+            if let Some(cursor) = cursor {
+                *current_index = cursor.index;
+                *frame_start = cursor.frame_start;
+                *frame_end = cursor.frame_end;
+            } else if let Some(index) =
+                came_from.and_then(|name| items.iter().position(|item| item.name == name))
+            {
+                *current_index = index as i32;
+                // the frame is computed when drawing
+                *frame_start = 0;
+                *frame_end = 0;
             }
-            info!("👉 step into: {}", current_url);
-            self.reset_frame();
         }
     }
 
+    pub async fn step_into(&mut self) -> Result<(), RequestError> {
+        info!("👉 step into");
+        let url = match (self.current_url(), self.selected_item()) {
+            (Some(current_url), Some(item)) if item.size == -1 => {
+                format!("{}{}/", current_url, item.name)
+            }
+            (Some(current_url), Some(item)) => format!("{}{}", current_url, item.name),
+            _ => {
+                info!("👉 nothing to step into");
+                return Ok(());
+            }
+        };
+        self.visit(url, None).await?;
+        info!("👉 step into: {}", self.current_url().unwrap_or_default());
+        Ok(())
+    }
+
     pub fn reset_frame(&mut self) {
         if let Self::Initialized {
             current_index,
@@ -355,29 +406,23 @@ impl AppState {
         }
     }
 
-    /// return 0 if it is a directory
-    /// return 1 if it is a file
-    pub fn get_state(&self) -> ContentState {
-        if let Self::Initialized { current_url, .. } = self {
-            if current_url.ends_with('/') {
-                ContentState::ItemList
-            } else {
-                ContentState::FileChunk
-            }
-        } else {
-            panic!("☹️ invalid state");
+    /// What is displayed, `None` before the state is initialized
+    pub fn get_state(&self) -> Option<ContentState> {
+        match self.current_url()? {
+            url if url.ends_with('/') => Some(ContentState::ItemList),
+            _ => Some(ContentState::FileChunk),
         }
     }
 
-    pub fn get_file_chunk(&self) -> String {
+    pub fn get_file_chunk(&self) -> Option<&str> {
         if let Self::Initialized {
             file_chunk: Some(file_chunk),
             ..
         } = self
         {
-            return file_chunk.clone();
+            Some(file_chunk)
         } else {
-            panic!("☹️ invalid state");
+            None
         }
     }
 
@@ -393,7 +438,7 @@ impl AppState {
         } = self
         {
             *last_index = *current_index;
-            *current_index = (*current_index - 1).clamp(0, current_items.len() as i32 - 1);
+            *current_index = (*current_index - 1).clamp(0, last(current_items.len()));
             if *current_index + 1 == *frame_end as i32
                 && current_items.len() > (*frame_end - *frame_start)
             {
@@ -411,7 +456,7 @@ impl AppState {
         {
             *last_index = *current_index;
             let lines_count = file_chunk.lines().count();
-            *current_index = (*current_index - 1).clamp(0, lines_count as i32 - 1);
+            *current_index = (*current_index - 1).clamp(0, last(lines_count));
             info!("new index: {}", current_index);
         }
     }
@@ -429,7 +474,7 @@ impl AppState {
         } = self
         {
             *last_index = *current_index;
-            *current_index = (*current_index + 1).clamp(0, current_items.len() as i32 - 1);
+            *current_index = (*current_index + 1).clamp(0, last(current_items.len()));
             if *current_index + 1 == *frame_end as i32
                 && current_items.len() > (*frame_end - *frame_start)
             {
@@ -447,31 +492,314 @@ impl AppState {
         {
             *last_index = *current_index;
             let lines_count = file_chunk.lines().count();
-            *current_index = (*current_index + 1).clamp(0, lines_count as i32 - 1);
+            *current_index = (*current_index + 1).clamp(0, last(lines_count));
             info!("new index: {}", current_index);
         }
     }
 
-    pub async fn save_file(&mut self) {
-        info!("🦁️ download");
+    /// The selected entry of the current directory
+    pub fn selected_item(&self) -> Option<&Item> {
         if let Self::Initialized {
             current_index,
             current_items: Some(items),
-            file_chunk,
+            ..
+        } = self
+        {
+            items.get(*current_index as usize)
+        } else {
+            None
+        }
+    }
+
+    /// The url of the displayed file, or of the selected entry of the displayed directory
+    /// (ending with a '/' for a directory)
+    pub fn selected_url(&self) -> Option<String> {
+        let current_url = match self {
+            Self::Initialized { current_url, .. } => current_url,
+            Self::Init => return None,
+        };
+        if !current_url.ends_with('/') {
+            return Some(current_url.clone());
+        }
+        self.selected_item().map(|item| {
+            if item.size == -1 {
+                format!("{}{}/", current_url, item.name)
+            } else {
+                format!("{}{}", current_url, item.name)
+            }
+        })
+    }
+
+    pub fn is_marked(&self, name: &str) -> bool {
+        matches!(self, Self::Initialized { marked, .. } if marked.contains(name))
+    }
+
+    pub fn count_marked(&self) -> usize {
+        match self {
+            Self::Initialized { marked, .. } => marked.len(),
+            Self::Init => 0,
+        }
+    }
+
+    /// Mark or unmark the selected entry, then select the next one
+    pub fn toggle_mark(&mut self) {
+        let name = match self.selected_item() {
+            Some(item) => item.name.clone(),
+            None => return,
+        };
+        if let Self::Initialized { marked, .. } = self {
+            if !marked.remove(&name) {
+                marked.insert(name);
+            }
+        }
+        self.move_down();
+    }
+
+    pub fn mark_all(&mut self) {
+        if let Self::Initialized {
+            current_items: Some(items),
+            marked,
+            ..
+        } = self
+        {
+            marked.extend(items.iter().map(|item| item.name.clone()));
+        }
+    }
+
+    pub fn invert_marks(&mut self) {
+        if let Self::Initialized {
+            current_items: Some(items),
+            marked,
+            ..
+        } = self
+        {
+            *marked = items
+                .iter()
+                .map(|item| item.name.clone())
+                .filter(|name| !marked.contains(name))
+                .collect();
+        }
+    }
+
+    /// Mark the entries matching the glob `pattern`, returns the number of matching entries
+    pub fn mark_glob(&mut self, pattern: &Pattern) -> usize {
+        if let Self::Initialized {
+            current_items: Some(items),
+            marked,
+            ..
+        } = self
+        {
+            let names = items
+                .iter()
+                .filter(|item| pattern.matches(&item.name))
+                .map(|item| item.name.clone())
+                .collect::<Vec<_>>();
+            let count = names.len();
+            marked.extend(names);
+            count
+        } else {
+            0
+        }
+    }
+
+    pub fn clear_marks(&mut self) {
+        if let Self::Initialized { marked, .. } = self {
+            marked.clear();
+        }
+    }
+
+    /// The urls of the marked entries, in the listing order
+    /// (ending with a '/' for directories)
+    pub fn marked_urls(&self) -> Vec<String> {
+        if let Self::Initialized {
+            current_url,
+            current_items: Some(items),
+            marked,
+            ..
+        } = self
+        {
+            items
+                .iter()
+                .filter(|item| marked.contains(&item.name))
+                .map(|item| {
+                    if item.size == -1 {
+                        format!("{}{}/", current_url, item.name)
+                    } else {
+                        format!("{}{}", current_url, item.name)
+                    }
+                })
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Reflect an entry created at `url` in the current directory, without listing it again,
+    /// the new entry gets selected
+    pub fn entry_created(&mut self, url: &str, size: i64) {
+        if let Self::Initialized {
             current_url,
+            current_index,
+            current_items: Some(items),
+            frame_start,
+            frame_end,
             ..
         } = self
         {
-            let mut current_url = current_url.to_owned();
-            current_url.push_str(&items[*current_index as usize].name);
-            info!("target file url : {}", current_url);
-            if let Err(e) = dump_file(&current_url, file_chunk).await {
-                error!("failed to dump {} to local file", e);
+            let (parent, name) = split_entry_url(url);
+            if parent != current_url {
+                return;
             }
+            let index = match items.iter().position(|item| item.name == name) {
+                Some(index) => index,
+                None => {
+                    items.push(Item {
+                        name: name.to_string(),
+                        size,
+                    });
+                    items.len() - 1
+                }
+            };
+            *current_index = index as i32;
+            // the frame is computed when drawing
+            *frame_start = 0;
+            *frame_end = 0;
+        }
+    }
+
+    /// Reflect the removal of the entry at `url` from the current directory,
+    /// returns the entry if it was listed
+    pub fn entry_removed(&mut self, url: &str) -> Option<Item> {
+        if let Self::Initialized {
+            current_url,
+            current_index,
+            current_items: Some(items),
+            frame_start,
+            frame_end,
+            marked,
+            ..
+        } = self
+        {
+            let (parent, name) = split_entry_url(url);
+            if parent != current_url {
+                return None;
+            }
+            let index = items.iter().position(|item| item.name == name)?;
+            let item = items.remove(index);
+            marked.remove(name);
+            *current_index = (*current_index).min(items.len() as i32 - 1).max(0);
+            *frame_start = 0;
+            *frame_end = 0;
+            Some(item)
+        } else {
+            None
+        }
+    }
+
+    /// Load the current location again, keeping the cursor
+    pub async fn refresh(&mut self) -> Result<(), RequestError> {
+        if let Some(url) = self.current_url().map(str::to_string) {
+            self.remember_cursor();
+            self.go_to(url).await?;
+        }
+        Ok(())
+    }
+
+    /// Show the fresh `items` of the directory at `url` when it is the current one,
+    /// keeping the cursor
+    pub fn listing_loaded(&mut self, url: &str, items: Vec<Item>) {
+        if self.current_url() != Some(url) {
+            return;
+        }
+        self.remember_cursor();
+        if let Self::Initialized {
+            current_index,
+            current_items,
+            frame_start,
+            frame_end,
+            marked,
+            ..
+        } = self
+        {
+            marked.retain(|name| items.iter().any(|item| &item.name == name));
+            *current_items = Some(items);
+            *current_index = 0;
+            *frame_start = 0;
+            *frame_end = 0;
+        }
+        self.restore_cursor(None);
+    }
+
+    /// The directory of the current url (the parent directory when a file is displayed)
+    pub fn current_dir(&self) -> Option<String> {
+        if let Self::Initialized { current_url, .. } = self {
+            let last_slash_index = current_url.rfind('/')?;
+            Some(current_url[..=last_slash_index].to_string())
+        } else {
+            None
+        }
+    }
+
+    pub fn usage(&self) -> Option<&DiskUsage> {
+        if let Self::Initialized {
+            usage: Some(usage), ..
+        } = self
+        {
+            Some(usage)
+        } else {
+            None
+        }
+    }
+
+    pub fn usage_mut(&mut self) -> Option<&mut DiskUsage> {
+        if let Self::Initialized {
+            usage: Some(usage), ..
+        } = self
+        {
+            Some(usage)
         } else {
-            warn!("trying to save directory but can not satifsy");
+            None
+        }
+    }
+
+    pub fn is_usage_shown(&self) -> bool {
+        self.usage().is_some()
+    }
+
+    /// Switch to the disk usage explorer, the scan has to be dispatched by the caller
+    pub fn show_usage(&mut self, root_url: String) {
+        info!("📊 disk usage of {}", root_url);
+        if let Self::Initialized { usage, .. } = self {
+            *usage = Some(Box::new(DiskUsage::new(root_url)));
+        }
+    }
+
+    pub fn hide_usage(&mut self) {
+        if let Self::Initialized { usage, .. } = self {
+            *usage = None;
+        }
+    }
+
+    /// Store a failed scan, ignored if the explorer was closed in the meantime
+    pub fn usage_scan_failed(&mut self, root_url: &str, error: String) {
+        match self.usage_mut() {
+            Some(usage) if usage.root_url() == root_url => usage.scan_failed(error),
+            _ => warn!("📊 scan of {} is no longer displayed", root_url),
         }
     }
+
+    /// Store a finished scan, ignored if the explorer was closed in the meantime
+    pub fn usage_scanned(&mut self, root_url: &str, root: UsageNode) {
+        match self.usage_mut() {
+            Some(usage) if usage.root_url() == root_url => usage.scanned(root),
+            _ => warn!("📊 scan of {} is no longer displayed", root_url),
+        }
+    }
+}
+
+/// The last index of `len` rows, 0 when there is none
+fn last(len: usize) -> i32 {
+    (len as i32 - 1).max(0)
 }
 
 impl Default for AppState {
@@ -479,3 +807,177 @@ impl Default for AppState {
         Self::Init
     }
 }
+
+/// Split the url of an entry into the url of its directory (ending with a '/') and its name
+fn split_entry_url(url: &str) -> (&str, &str) {
+    let url = url.trim_end_matches('/');
+    match url.rfind('/') {
+        Some(index) => (&url[..=index], &url[index + 1..]),
+        None => ("", url),
+    }
+}
+
+#[cfg(test)]
+impl AppState {
+    /// A state browsing the directory at `url`, listed as `items`
+    pub(crate) fn browsing(url: &str, items: Vec<Item>) -> Self {
+        Self::Initialized {
+            duration: Duration::from_secs(1),
+            counter_sleep: 0,
+            counter_tick: 0,
+            current_url: url.to_string(),
+            current_index: 0,
+            last_index: 0,
+            current_items: Some(items),
+            show_file: false,
+            frame_start: 0,
+            frame_end: 0,
+            file_chunk: None,
+            usage: None,
+            history: Box::default(),
+            marked: HashSet::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, size: i64) -> Item {
+        Item {
+            name: name.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn should_mark_remote_entries() {
+        let url = "http://localhost:7878/user/alice/";
+        let items = vec![
+            item("logs", -1),
+            item("a.csv", 12),
+            item("b.csv", 34),
+            item("c.txt", 56),
+        ];
+        let mut state = AppState::browsing(url, items);
+        assert!(state.marked_urls().is_empty());
+
+        assert_eq!(state.mark_glob(&Pattern::new("*.csv").unwrap()), 2);
+        assert_eq!(
+            state.marked_urls(),
+            vec![format!("{}a.csv", url), format!("{}b.csv", url)]
+        );
+
+        state.invert_marks();
+        assert_eq!(
+            state.marked_urls(),
+            vec![format!("{}logs/", url), format!("{}c.txt", url)]
+        );
+        assert_eq!(state.count_marked(), 2);
+    }
+
+    #[test]
+    fn should_apply_fresh_listing() {
+        let url = "http://localhost:7878/user/alice/";
+        let mut state = AppState::browsing(url, vec![item("a.csv", 12), item("b.csv", 34)]);
+        state.move_down();
+        state.toggle_mark();
+
+        state.listing_loaded("http://localhost:7878/user/bob/", Vec::new());
+        assert_eq!(state.rows().len(), 2);
+
+        state.listing_loaded(url, vec![item("a.csv", 12), item("c.csv", 56)]);
+        assert_eq!(state.rows().len(), 2);
+        assert_eq!(state.get_index(), 1);
+        assert_eq!(state.count_marked(), 0);
+    }
+
+    #[test]
+    fn should_add_and_remove_entries() {
+        let url = "http://localhost:7878/user/alice/";
+        let mut state = AppState::browsing(url, vec![item("a.csv", 12), item("b.csv", 34)]);
+        state.move_down();
+        state.toggle_mark();
+
+        state.entry_created("http://localhost:7878/user/bob/logs/", -1);
+        assert_eq!(state.rows().len(), 2);
+        state.entry_created(&format!("{}logs/", url), -1);
+        assert_eq!(state.rows().len(), 3);
+        assert_eq!(state.get_index(), 2);
+        assert_eq!(state.selected_url().unwrap(), format!("{}logs/", url));
+
+        assert!(state.entry_removed(&format!("{}c.csv", url)).is_none());
+        let removed = state.entry_removed(&format!("{}b.csv", url)).unwrap();
+        assert_eq!(removed.size, 34);
+        assert_eq!(state.rows().len(), 2);
+        assert_eq!(state.count_marked(), 0);
+        state.entry_removed(&format!("{}logs/", url));
+        assert_eq!(state.get_index(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_browse_empty_directory() {
+        let mut state = AppState::browsing("http://localhost:7878/user/alice/", Vec::new());
+        state.move_down();
+        state.move_up();
+        assert_eq!(state.get_index(), 0);
+        assert!(state.step_into().await.is_ok());
+        assert_eq!(
+            state.current_dir().unwrap(),
+            "http://localhost:7878/user/alice/"
+        );
+        assert!(AppState::Init.get_state().is_none());
+        assert!(AppState::Init.get_file_chunk().is_none());
+    }
+
+    #[tokio::test]
+    async fn should_retry_listing_once() {
+        use crate::request::stand_in::{response, stand_in_proxy};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let requests = Arc::new(AtomicUsize::new(0));
+        let counted = requests.clone();
+        let origin = stand_in_proxy(move |_| match counted.fetch_add(1, Ordering::SeqCst) {
+            0 => response("503 Service Unavailable", ""),
+            _ => response("200 OK", r#"[{"name":"a.log","size":12}]"#),
+        })
+        .await;
+        let url = format!("{}/user/alice/", origin);
+        let mut state = AppState::browsing(&url, vec![item("logs", -1)]);
+
+        state.step_into().await.unwrap();
+        assert_eq!(state.current_url(), Some(format!("{}logs/", url).as_str()));
+        assert_eq!(state.rows().len(), 1);
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn should_stay_at_the_root() {
+        // nothing listens on the port 1, going up would fail
+        let url = "http://127.0.0.1:1/";
+        let mut state = AppState::browsing(url, vec![item("user", -1)]);
+        assert!(state.back_to_previours().await.is_ok());
+        assert_eq!(state.current_url(), Some(url));
+        assert_eq!(state.rows().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_keep_view_on_failure() {
+        // nothing listens on the port 1
+        let url = "http://127.0.0.1:1/user/alice/";
+        let items = vec![item("data.csv", 12)];
+        let mut state = AppState::browsing(url, items);
+        state.move_down();
+
+        assert!(state.step_into().await.is_err());
+        assert_eq!(state.current_dir().unwrap(), url);
+        assert_eq!(state.rows().len(), 1);
+        assert!(matches!(state.get_state(), Some(ContentState::ItemList)));
+
+        assert!(state.refresh().await.is_err());
+        assert_eq!(state.current_dir().unwrap(), url);
+        assert!(AppState::initialized(url.to_string()).await.is_err());
+    }
+}
@@ -4,8 +4,11 @@ use symbols::line;
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Modifier, Style};
-use tui::text::{Span, Spans};
-use tui::widgets::{Block, BorderType, Borders, Cell, LineGauge, Paragraph, Row, Table, Wrap};
+use tui::text::{Span, Spans, Text};
+use tui::widgets::{
+    Block, BorderType, Borders, Cell, Clear, LineGauge, Paragraph, Row, Table, TableState, Tabs,
+    Wrap,
+};
 use tui::{symbols, Frame};
 use tui_logger::TuiLoggerWidget;
 use std::cmp::min;
@@ -15,6 +18,14 @@ use super::state::AppState;
 use crate::app::App;
 use crate::app::state::Item;
 use crate::app::state::ContentState;
+use crate::app::bookmarks::Bookmarks;
+use crate::config::Connection;
+use crate::app::local::{LocalPane, Pane};
+use crate::app::popup::Popup;
+use crate::app::properties::{Properties, FIELDS};
+use crate::app::prompt::{split_parent, Prompt};
+use crate::app::usage::{human_size, DiskUsage};
+use crate::request::split_origin;
 use log::info;
 use lazy_static::lazy_static;
 
@@ -31,6 +42,7 @@ where
         .constraints(
             [
                 Constraint::Length(3),
+                Constraint::Length(1),
                 Constraint::Min(10),
                 Constraint::Length(3),
                 Constraint::Length(12),
@@ -43,39 +55,109 @@ where
     let title = draw_title();
     rect.render_widget(title, chunks[0]);
 
+    // Tabs
+    let tabs = draw_tabs(app.tabs(), app.tab_index());
+    rect.render_widget(tabs, chunks[1]);
+
     // Body & Help
     let body_chunks = Layout::default()
         .direction(Direction::Horizontal)
         .constraints([Constraint::Min(20), Constraint::Length(32)].as_ref())
-        .split(chunks[1]);
+        .split(chunks[2]);
+
+    // Commander: the local pane on the left of the remote one
+    let remote_area = if let Some(commander) = app.commander() {
+        let pane_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(body_chunks[0]);
+        let mut table_state = TableState::default();
+        table_state.select(Some(commander.local.index()));
+        let local = draw_local(&commander.local, commander.focus == Pane::Local);
+        rect.render_stateful_widget(local, pane_chunks[0], &mut table_state);
+        pane_chunks[1]
+    } else {
+        body_chunks[0]
+    };
 
     let state = app.state();
-    if state.is_initialized() {
+    if let Some(usage) = state.usage() {
+        let mut table_state = TableState::default();
+        table_state.select(Some(usage.index()));
+        let body = draw_body_usage(usage);
+        rect.render_stateful_widget(body, remote_area, &mut table_state);
+    } else if state.is_initialized() {
         match state.get_state(){
-            ContentState::ItemList => {
-                let body = draw_body_dir(app.is_loading(), app.state(), chunks[1].height);
-                rect.render_widget(body, body_chunks[0]);
+            Some(ContentState::ItemList) => {
+                let body = draw_body_dir(app.is_loading(), app.state(), chunks[2].height);
+                rect.render_widget(body, remote_area);
             },
-            ContentState::FileChunk => {
-                let body = draw_body_file(app.is_loading(), app.state(), chunks[1].height);
-                rect.render_widget(body, body_chunks[0]);
+            Some(ContentState::FileChunk) => {
+                let body = draw_body_file(app.is_loading(), app.state(), chunks[2].height);
+                rect.render_widget(body, remote_area);
             },
             _ => {}
         }
     }
 
+    match app.popup() {
+        Some(Popup::Bookmarks { index }) => {
+            let area = centered_rect(80, 60, body_chunks[0]);
+            let mut table_state = TableState::default();
+            table_state.select(Some(*index));
+            rect.render_widget(Clear, area);
+            rect.render_stateful_widget(draw_bookmarks(app.bookmarks()), area, &mut table_state);
+        }
+        Some(Popup::Connections { index }) => {
+            let area = centered_rect(80, 60, body_chunks[0]);
+            let mut table_state = TableState::default();
+            table_state.select(Some(*index));
+            rect.render_widget(Clear, area);
+            let connections = draw_connections(app.connections(), app.connection());
+            rect.render_stateful_widget(connections, area, &mut table_state);
+        }
+        Some(Popup::ConfirmDelete { urls, recursive }) => {
+            let area = centered_rect(80, 60, body_chunks[0]);
+            rect.render_widget(Clear, area);
+            rect.render_widget(draw_confirm_delete(urls, *recursive), area);
+        }
+        Some(Popup::Properties(properties)) => {
+            let area = centered_rect(80, 60, body_chunks[0]);
+            rect.render_widget(Clear, area);
+            rect.render_widget(draw_properties(properties), area);
+        }
+        Some(Popup::Error(message)) => {
+            let area = centered_rect(80, 20, body_chunks[0]);
+            rect.render_widget(Clear, area);
+            rect.render_widget(draw_error(message), area);
+        }
+        None => {}
+    }
+
+    if let Some(prompt) = app.prompt() {
+        let area = centered_rect(80, 0, body_chunks[0]);
+        let area = Rect { height: 3, ..area };
+        rect.render_widget(Clear, area);
+        rect.render_widget(draw_prompt(prompt), area);
+    }
+
     let help = draw_help(app.actions());
     rect.render_widget(help, body_chunks[1]);
 
     // Duration LineGauge
-    if let AppState::Initialized {current_url,..} = app.state() {
-        let duration_block = draw_current_url(current_url);
-        rect.render_widget(duration_block, chunks[2]);
+    if let Some(usage) = app.state().usage() {
+        let usage_url = usage.current_url();
+        let duration_block = draw_current_url(&usage_url, app.batch());
+        rect.render_widget(duration_block, chunks[3]);
+    } else if let AppState::Initialized {current_url,..} = app.state() {
+        let current_url = current_url.clone();
+        let duration_block = draw_current_url(&current_url, app.batch());
+        rect.render_widget(duration_block, chunks[3]);
     }
 
     // Logs
     let logs = draw_logs();
-    rect.render_widget(logs, chunks[3]);
+    rect.render_widget(logs, chunks[4]);
 }
 
 fn draw_title<'a>() -> Paragraph<'a> {
@@ -90,17 +172,44 @@ fn draw_title<'a>() -> Paragraph<'a> {
         )
 }
 
+fn draw_tabs<'a>(tabs: &[AppState], selected: usize) -> Tabs<'a> {
+    let titles = tabs
+        .iter()
+        .enumerate()
+        .map(|(index, tab)| {
+            let name = match tab {
+                AppState::Initialized { current_url, .. } => {
+                    let (_, path) = split_origin(current_url);
+                    let (_, name) = split_parent(path.trim_end_matches('/'));
+                    if path.ends_with('/') {
+                        format!("{}/", name)
+                    } else {
+                        name.to_string()
+                    }
+                }
+                AppState::Init => String::from("..."),
+            };
+            Spans::from(format!("{}: {}", index + 1, name))
+        })
+        .collect::<Vec<_>>();
+
+    Tabs::new(titles)
+        .select(selected)
+        .style(Style::default().fg(Color::Gray))
+        .highlight_style(Style::default().fg(Color::LightGreen))
+}
+
 fn check_size(rect: &Rect) {
     if rect.width < 52 {
         panic!("Require width >= 52, (got {})", rect.width);
     }
-    if rect.height < 28 {
-        panic!("Require height >= 28, (got {})", rect.height);
+    if rect.height < 29 {
+        panic!("Require height >= 29, (got {})", rect.height);
     }
 }
 
 fn draw_body_file<'a>(loading:bool, state:&mut AppState, height:u16) -> Paragraph<'a>{
-    let text_content = state.get_file_chunk();
+    let text_content = state.get_file_chunk().unwrap_or_default().to_string();
     let highlight_index = state.get_index();
     let lines = text_content.lines().map(|line| line.to_string()).collect::<Vec<_>>();
     if state.get_frame() == (0, 0){
@@ -141,8 +250,10 @@ fn draw_body_dir<'a>(loading: bool, state: &mut AppState, height:u16) -> Table<'
     let highlight_index = state.get_index();
     let table_rows = rows.iter().
         enumerate().map(|(index, row)| {
+            let marker = if state.is_marked(&row.name) { "*" } else { " " };
             let baes_row = if row.size == -1{
                 Row::new(vec![ 
+                    Cell::from(Span::styled(marker, Style::default().fg(Color::Yellow))),
                     Cell::from(Span::raw("d")),
                     Cell::from(Span::raw(row.name.clone())),
                     Cell::from(Span::raw("-")),
@@ -150,6 +261,7 @@ fn draw_body_dir<'a>(loading: bool, state: &mut AppState, height:u16) -> Table<'
             }
             else{
                 Row::new(vec![ 
+                    Cell::from(Span::styled(marker, Style::default().fg(Color::Yellow))),
                     Cell::from(Span::raw("f")),
                     Cell::from(Span::raw(row.name.clone())),
                     Cell::from(Span::raw(row.size.to_string())),
@@ -158,23 +270,37 @@ fn draw_body_dir<'a>(loading: bool, state: &mut AppState, height:u16) -> Table<'
             };
             if index as i32 == highlight_index {
                 baes_row.style(Style::default().fg(Color::LightGreen))
+            } else if state.is_marked(&row.name) {
+                baes_row.style(Style::default().fg(Color::Yellow))
             } else {
                 baes_row
             }
         }).collect::<Vec<_>>();
     if state.is_initialized() && state.get_frame() == (0, 0){
         info!("table_rows:{}",table_rows.len());
-        state.set_frame(0, min(table_rows.len(), (height - 3) as usize));
+        // Keep the selected row visible, it may have been restored from the history
+        let visible = min(table_rows.len(), (height - 3) as usize);
+        let start = (highlight_index.max(0) as usize + 1)
+            .saturating_sub(visible)
+            .min(table_rows.len() - visible);
+        state.set_frame(start, start + visible);
         info!("frame size:{:?}", state.get_frame());
     }
 
     let (frame_start, frame_end) = state.get_frame();
     let table_rows = table_rows[frame_start..frame_end].to_owned();
 
+    let marked = state.count_marked();
+    let title = if marked > 0 {
+        format!("Files ({} marked)", marked)
+    } else {
+        String::from("Files")
+    };
+
     // head and contents height
     let table = Table::new(table_rows.to_owned())
         .header(
-            Row::new(vec!["Type", "Name", "Size"])
+            Row::new(vec![" ", "Type", "Name", "Size"])
                 .style(Style::default().fg(Color::Yellow))
                 .height(1)
                 .bottom_margin(1),
@@ -182,10 +308,11 @@ fn draw_body_dir<'a>(loading: bool, state: &mut AppState, height:u16) -> Table<'
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Files")
+                .title(title)
                 .border_type(BorderType::Plain),
         )
         .widths(&[
+            Constraint::Length(1),
             Constraint::Length(5),
             Constraint::Length(20),
             Constraint::Length(10),
@@ -194,12 +321,311 @@ fn draw_body_dir<'a>(loading: bool, state: &mut AppState, height:u16) -> Table<'
 
 }
 
-fn draw_current_url(current_url:&str) -> Paragraph {
+fn draw_body_usage<'a>(usage: &DiskUsage) -> Table<'a> {
+    let title = if let Some(error) = usage.error() {
+        format!("Disk usage (scan failed: {})", error)
+    } else if usage.is_scanning() {
+        String::from("Disk usage (scanning...)")
+    } else {
+        String::from("Disk usage")
+    };
+    let (total, children) = match usage.current() {
+        Some(node) => (node.size, node.children.as_slice()),
+        None => (0, [].as_slice()),
+    };
+
+    let table_rows = children
+        .iter()
+        .map(|child| {
+            let ratio = if total == 0 {
+                0.0
+            } else {
+                child.size as f64 / total as f64
+            };
+            let filled = (ratio * 10.0).round() as usize;
+            let bar = format!("{}{}", "#".repeat(filled), " ".repeat(10 - filled));
+            let name = if child.is_dir {
+                format!("{}/", child.name)
+            } else {
+                child.name.clone()
+            };
+            Row::new(vec![
+                Cell::from(Span::raw(human_size(child.size))),
+                Cell::from(Span::styled(format!("{:5.1}% [{}]", ratio * 100.0, bar), Style::default().fg(Color::LightCyan))),
+                Cell::from(Span::raw(name)),
+            ])
+            .height(1)
+        })
+        .collect::<Vec<_>>();
+
+    Table::new(table_rows)
+        .header(
+            Row::new(vec!["Size", "Usage", "Name"])
+                .style(Style::default().fg(Color::Yellow))
+                .height(1)
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("{} - {}", title, human_size(total)))
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(Style::default().fg(Color::LightGreen))
+        .widths(&[
+            Constraint::Length(10),
+            Constraint::Length(19),
+            Constraint::Min(20),
+        ])
+}
+
+fn draw_local<'a>(local: &LocalPane, focused: bool) -> Table<'a> {
+    let rows = local
+        .items()
+        .iter()
+        .map(|item| {
+            let (kind, size) = if item.size == -1 {
+                ("d", String::from("-"))
+            } else {
+                ("f", item.size.to_string())
+            };
+            let marker = if local.is_marked(&item.name) { "*" } else { " " };
+            let row = Row::new(vec![
+                Cell::from(Span::styled(marker, Style::default().fg(Color::Yellow))),
+                Cell::from(Span::raw(kind)),
+                Cell::from(Span::raw(item.name.clone())),
+                Cell::from(Span::raw(size)),
+            ])
+            .height(1);
+            if local.is_marked(&item.name) {
+                row.style(Style::default().fg(Color::Yellow))
+            } else {
+                row
+            }
+        })
+        .collect::<Vec<_>>();
+    let title = match local.count_marked() {
+        0 => format!("Local {}", local.dir().display()),
+        marked => format!("Local {} ({} marked)", local.dir().display(), marked),
+    };
+    let border_style = if focused {
+        Style::default().fg(Color::LightCyan)
+    } else {
+        Style::default().fg(Color::White)
+    };
+
+    Table::new(rows)
+        .header(
+            Row::new(vec![" ", "Type", "Name", "Size"])
+                .style(Style::default().fg(Color::Yellow))
+                .height(1)
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(border_style)
+                .title(title)
+                .border_type(BorderType::Plain),
+        )
+        .highlight_style(Style::default().fg(Color::LightGreen))
+        .widths(&[
+            Constraint::Length(1),
+            Constraint::Length(5),
+            Constraint::Length(20),
+            Constraint::Length(10),
+        ])
+}
+
+fn draw_bookmarks<'a>(bookmarks: &Bookmarks) -> Table<'a> {
+    let rows = bookmarks
+        .list()
+        .iter()
+        .map(|bookmark| {
+            Row::new(vec![
+                Cell::from(Span::raw(bookmark.name.clone())),
+                Cell::from(Span::raw(bookmark.url.clone())),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let title = if bookmarks.is_empty() {
+        "Bookmarks (none, use <b> to add one)"
+    } else {
+        "Bookmarks"
+    };
+
+    Table::new(rows)
+        .header(
+            Row::new(vec!["Name", "Url"])
+                .style(Style::default().fg(Color::Yellow))
+                .height(1)
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_type(BorderType::Double),
+        )
+        .highlight_style(Style::default().fg(Color::LightGreen))
+        .widths(&[Constraint::Length(20), Constraint::Min(20)])
+}
+
+fn draw_connections<'a>(connections: &[Connection], active: &str) -> Table<'a> {
+    let rows = connections
+        .iter()
+        .map(|connection| {
+            let marker = if connection.name == active { "*" } else { "" };
+            Row::new(vec![
+                Cell::from(Span::raw(marker)),
+                Cell::from(Span::raw(connection.name.clone())),
+                Cell::from(Span::raw(connection.base_url.clone().unwrap_or_default())),
+            ])
+        })
+        .collect::<Vec<_>>();
+    let title = if connections.is_empty() {
+        "Connections (none, add profiles to the config file)"
+    } else {
+        "Connections"
+    };
+
+    Table::new(rows)
+        .header(
+            Row::new(vec!["", "Name", "Url"])
+                .style(Style::default().fg(Color::Yellow))
+                .height(1)
+                .bottom_margin(1),
+        )
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_type(BorderType::Double),
+        )
+        .highlight_style(Style::default().fg(Color::LightGreen))
+        .widths(&[
+            Constraint::Length(1),
+            Constraint::Length(20),
+            Constraint::Min(20),
+        ])
+}
+
+fn draw_confirm_delete<'a>(urls: &[String], recursive: bool) -> Paragraph<'a> {
+    let mut lines = vec![
+        Spans::from(Span::styled(
+            format!("Delete {} item(s)? recursive: {}", urls.len(), if recursive { "yes" } else { "no" }),
+            Style::default().fg(Color::LightRed),
+        )),
+        Spans::from(""),
+    ];
+    lines.extend(urls.iter().map(|url| Spans::from(Span::raw(url.clone()))));
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Delete (<y> delete, <t> move to trash, <r> toggle recursive, <n> cancel)")
+                .border_type(BorderType::Double),
+        )
+        .style(Style::default().fg(Color::White))
+}
+
+fn draw_error<'a>(message: &str) -> Paragraph<'a> {
+    Paragraph::new(Text::styled(message.to_string(), Style::default().fg(Color::LightRed)))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Error (<esc> close)")
+                .border_type(BorderType::Double),
+        )
+        .wrap(Wrap{trim: true})
+        .style(Style::default().fg(Color::White))
+}
+
+fn draw_properties<'a>(properties: &Properties) -> Paragraph<'a> {
+    let status = properties.status();
+    let loaded = [
+        status.permission.clone(),
+        status.owner.clone(),
+        status.group.clone(),
+        status.replication.to_string(),
+    ];
+    let mut lines = FIELDS
+        .iter()
+        .zip(loaded.iter().zip(properties.values()))
+        .enumerate()
+        .map(|(index, (field, (loaded, value)))| {
+            let value = if properties.is_dir() && index == FIELDS.len() - 1 {
+                String::from("-")
+            } else if loaded != value {
+                format!("{} -> {}", loaded, value)
+            } else {
+                value.clone()
+            };
+            let style = if index == properties.index() && properties.preview().is_none() {
+                Style::default().fg(Color::LightGreen)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Spans::from(Span::styled(format!("{:<12} {}", field, value), style))
+        })
+        .collect::<Vec<_>>();
+    lines.push(Spans::from(""));
+    if let Some(changes) = properties.preview() {
+        lines.push(Spans::from(Span::styled("Dry run, the following will be sent:", Style::default().fg(Color::Yellow))));
+        lines.extend(changes.iter().map(|change| Spans::from(format!("{} {}", change, properties.url()))));
+    } else if let Some(error) = properties.error() {
+        lines.push(Spans::from(Span::styled(error.to_string(), Style::default().fg(Color::LightRed))));
+    }
+    let title = if properties.preview().is_some() {
+        "Properties - dry run (<y> apply, <n> back to edit)"
+    } else {
+        "Properties (<e> edit, <s> preview changes, <Esc> close)"
+    };
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_type(BorderType::Double),
+        )
+}
+
+fn draw_prompt<'a>(prompt: &Prompt) -> Paragraph<'a> {
+    Paragraph::new(Spans::from(vec![
+        Span::raw(prompt.input().to_string()),
+        Span::styled("_", Style::default().fg(Color::LightCyan)),
+    ]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("{} (<Enter> to validate, <Esc> to cancel)", prompt.kind().title()))
+            .border_type(BorderType::Double),
+    )
+    .style(Style::default().fg(Color::White))
+}
+
+/// A rectangle centered in `area`, sized in percent of it
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let width = area.width * percent_x / 100;
+    let height = area.height * percent_y / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
+
+fn draw_current_url(current_url: &str, batch: Option<(usize, usize)>) -> Paragraph {
+    let title = match batch {
+        Some((done, total)) => format!("Current URL - batch {}/{}", done + 1, total),
+        None => String::from("Current URL"),
+    };
     Paragraph::new(current_url)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Current URL")
+                .title(title)
                 .border_type(BorderType::Plain),
         )
         .style(Style::default().fg(Color::White))
@@ -0,0 +1,290 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use log::{info, warn};
+
+use crate::request::{get_item_list, Result};
+
+/// A scanned entry, directories hold the aggregated size of their children
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UsageNode {
+    pub name: String,
+    pub size: u64,
+    pub is_dir: bool,
+    pub children: Vec<UsageNode>,
+}
+
+impl UsageNode {
+    pub fn file(name: &str, size: u64) -> Self {
+        Self {
+            name: name.to_string(),
+            size,
+            is_dir: false,
+            children: Vec::new(),
+        }
+    }
+
+    /// Build a directory node, children are sorted by size (biggest first)
+    pub fn dir(name: &str, mut children: Vec<UsageNode>) -> Self {
+        children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+        let size = children.iter().map(|child| child.size).sum();
+        Self {
+            name: name.to_string(),
+            size,
+            is_dir: true,
+            children,
+        }
+    }
+
+    /// Follow the names in `path` from this node
+    pub fn find(&self, path: &[String]) -> Option<&UsageNode> {
+        path.iter().try_fold(self, |node, name| {
+            node.children.iter().find(|child| &child.name == name)
+        })
+    }
+}
+
+/// Recursively scan the directory at `url` (which must end with a '/')
+///
+/// Sub-directories that can not be listed are logged and counted as empty.
+pub fn scan(url: String, name: String) -> Pin<Box<dyn Future<Output = Result<UsageNode>> + Send>> {
+    Box::pin(async move {
+        let items = get_item_list(&url).await?;
+        let mut children = Vec::with_capacity(items.len());
+        for item in items {
+            if item.size == -1 {
+                let child_url = format!("{}{}/", url, item.name);
+                match scan(child_url.clone(), item.name.clone()).await {
+                    Ok(node) => children.push(node),
+                    Err(e) => {
+                        warn!("📊 can not scan {}: {}", child_url, e);
+                        children.push(UsageNode::dir(&item.name, Vec::new()));
+                    }
+                }
+            } else {
+                children.push(UsageNode::file(&item.name, item.size.max(0) as u64));
+            }
+        }
+        info!("📊 scanned {}", url);
+        Ok(UsageNode::dir(&name, children))
+    })
+}
+
+/// The disk usage explorer state
+#[derive(Clone, Debug)]
+pub struct DiskUsage {
+    /// The directory where the scan started
+    root_url: String,
+    /// `None` while the scan is running
+    root: Option<UsageNode>,
+    /// Why the scan failed, the root can not be listed
+    error: Option<String>,
+    /// Names of the directories we drilled into, from the root
+    path: Vec<String>,
+    index: usize,
+}
+
+impl DiskUsage {
+    pub fn new(root_url: String) -> Self {
+        Self {
+            root_url,
+            root: None,
+            error: None,
+            path: Vec::new(),
+            index: 0,
+        }
+    }
+
+    pub fn root_url(&self) -> &str {
+        &self.root_url
+    }
+
+    pub fn is_scanning(&self) -> bool {
+        self.root.is_none() && self.error.is_none()
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Mark the tree as being scanned again, the drill-down path is kept
+    pub fn rescan(&mut self) {
+        self.root = None;
+        self.error = None;
+    }
+
+    /// The scan stopped as its root could not be listed
+    pub fn scan_failed(&mut self, error: String) {
+        self.root = None;
+        self.error = Some(error);
+    }
+
+    /// Store the scan result, keeping as much of the drill-down path as possible
+    pub fn scanned(&mut self, root: UsageNode) {
+        while !self.path.is_empty() && root.find(&self.path).is_none() {
+            self.path.pop();
+            self.index = 0;
+        }
+        self.root = Some(root);
+        self.error = None;
+        self.clamp_index();
+    }
+
+    /// The url of the directory currently displayed
+    pub fn current_url(&self) -> String {
+        let mut url = self.root_url.clone();
+        for name in self.path.iter() {
+            url.push_str(name);
+            url.push('/');
+        }
+        url
+    }
+
+    /// The directory currently displayed
+    pub fn current(&self) -> Option<&UsageNode> {
+        self.root.as_ref().and_then(|root| root.find(&self.path))
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn move_up(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.index += 1;
+        self.clamp_index();
+    }
+
+    pub fn step_into(&mut self) {
+        let selected = self
+            .current()
+            .and_then(|node| node.children.get(self.index))
+            .filter(|child| child.is_dir)
+            .map(|child| child.name.clone());
+        if let Some(name) = selected {
+            self.path.push(name);
+            self.index = 0;
+        } else {
+            info!("📊 only directories can be explored");
+        }
+    }
+
+    pub fn back_to_previous(&mut self) {
+        if let Some(name) = self.path.pop() {
+            // Land on the directory we came from
+            self.index = self
+                .current()
+                .and_then(|node| node.children.iter().position(|child| child.name == name))
+                .unwrap_or(0);
+        } else {
+            info!("📊 already at the root of the scan");
+        }
+    }
+
+    fn clamp_index(&mut self) {
+        let len = self.current().map(|node| node.children.len()).unwrap_or(0);
+        self.index = self.index.min(len.saturating_sub(1));
+    }
+}
+
+/// Format a size in bytes with a binary unit
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree() -> UsageNode {
+        UsageNode::dir(
+            "",
+            vec![
+                UsageNode::file("small", 10),
+                UsageNode::dir(
+                    "logs",
+                    vec![UsageNode::file("a.log", 100), UsageNode::file("b.log", 200)],
+                ),
+                UsageNode::dir("empty", vec![]),
+            ],
+        )
+    }
+
+    #[test]
+    fn should_aggregate_and_sort_children() {
+        let root = tree();
+        assert_eq!(root.size, 310);
+        let names = root
+            .children
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["logs", "small", "empty"]);
+        assert_eq!(root.children[0].children[0].name, "b.log");
+    }
+
+    #[test]
+    fn should_drill_down_and_back() {
+        let mut usage = DiskUsage::new("http://host/data/".to_string());
+        assert!(usage.is_scanning());
+        usage.scanned(tree());
+        usage.step_into();
+        assert_eq!(usage.current_url(), "http://host/data/logs/");
+        assert_eq!(usage.current().unwrap().size, 300);
+
+        usage.back_to_previous();
+        assert_eq!(usage.current_url(), "http://host/data/");
+        assert_eq!(usage.index(), 0);
+    }
+
+    #[test]
+    fn should_keep_path_on_rescan() {
+        let mut usage = DiskUsage::new("http://host/".to_string());
+        usage.scanned(tree());
+        usage.step_into();
+        usage.rescan();
+        usage.scanned(tree());
+        assert_eq!(usage.current_url(), "http://host/logs/");
+
+        usage.rescan();
+        usage.scanned(UsageNode::dir("", vec![]));
+        assert_eq!(usage.current_url(), "http://host/");
+    }
+
+    #[test]
+    fn should_format_human_size() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 K");
+        assert_eq!(human_size(3 * 1024 * 1024 * 1024), "3.0 G");
+    }
+
+    #[tokio::test]
+    async fn should_stop_scanning_on_failure() {
+        // nothing listens on the port 1
+        let root_url = "http://127.0.0.1:1/data/".to_string();
+        let mut usage = DiskUsage::new(root_url.clone());
+        let error = scan(root_url, String::new()).await.unwrap_err();
+        usage.scan_failed(error.to_string());
+        assert!(!usage.is_scanning());
+        assert!(usage.error().is_some());
+        assert!(usage.current().is_none());
+
+        usage.rescan();
+        assert!(usage.is_scanning());
+        assert!(usage.error().is_none());
+    }
+}
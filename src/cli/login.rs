@@ -0,0 +1,133 @@
+use std::io::{BufRead, Write};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use eyre::{eyre, Result};
+
+use crate::config::Connection;
+use crate::request::credentials::{self, Credentials};
+
+/// Ask the account and the key of `connection` on the terminal, before the TUI is started,
+/// then offer to save them to its access file. `None` when the user gives up.
+pub fn prompt_credentials(connection: &Connection) -> Result<Option<Credentials>> {
+    let stdin = std::io::stdin();
+    let mut input = stdin.lock();
+    let mut out = std::io::stderr();
+    writeln!(
+        out,
+        "Enter the credentials of the connection {:?} (an empty account to quit)",
+        connection.name
+    )?;
+
+    let known = connection
+        .account
+        .clone()
+        .or_else(|| credentials::from_env(credentials::ACCOUNT_VAR));
+    let account = ask(&mut input, &mut out, "account", known.as_deref())?;
+    if account.is_empty() {
+        return Ok(None);
+    }
+    write!(out, "key: ")?;
+    out.flush()?;
+    let key = match read_hidden()? {
+        Some(key) if !key.is_empty() => key,
+        _ => return Ok(None),
+    };
+    let credentials = Credentials {
+        account,
+        key,
+        auth: connection.auth,
+        signer: connection.signer.clone(),
+        negotiate: connection.negotiate.clone(),
+    };
+
+    let question = format!("save them to {}? [y/N]", connection.access_file.display());
+    if is_yes(&ask(&mut input, &mut out, &question, None)?) {
+        credentials.save(&connection.access_file)?;
+        writeln!(out, "saved to {}", connection.access_file.display())?;
+    }
+    Ok(Some(credentials))
+}
+
+/// Print `question` and read the answer, the `default` is shown and used for an empty answer
+fn ask<R: BufRead, W: Write>(
+    input: &mut R,
+    out: &mut W,
+    question: &str,
+    default: Option<&str>,
+) -> Result<String> {
+    match default {
+        Some(default) => write!(out, "{} [{}]: ", question, default)?,
+        None => write!(out, "{}: ", question)?,
+    }
+    out.flush()?;
+    let mut answer = String::new();
+    if input.read_line(&mut answer)? == 0 {
+        return Err(eyre!("no answer, the input is closed"));
+    }
+    let answer = answer.trim();
+    match default {
+        Some(default) if answer.is_empty() => Ok(default.to_string()),
+        _ => Ok(answer.to_string()),
+    }
+}
+
+fn is_yes(answer: &str) -> bool {
+    matches!(answer.to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Read a line without echoing it, `None` when cancelled with Esc or Ctrl-C
+fn read_hidden() -> Result<Option<String>> {
+    crossterm::terminal::enable_raw_mode()?;
+    let line = read_hidden_keys();
+    crossterm::terminal::disable_raw_mode()?;
+    eprintln!();
+    line
+}
+
+fn read_hidden_keys() -> Result<Option<String>> {
+    let mut line = String::new();
+    loop {
+        if let Event::Key(KeyEvent { code, modifiers }) = event::read()? {
+            match code {
+                KeyCode::Enter => return Ok(Some(line)),
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+                KeyCode::Char(c) => line.push(c),
+                KeyCode::Backspace => {
+                    line.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_ask_with_default() {
+        let mut out = Vec::new();
+        let answer = ask(&mut "\n".as_bytes(), &mut out, "account", Some("alice")).unwrap();
+        assert_eq!(answer, "alice");
+        assert_eq!(String::from_utf8(out).unwrap(), "account [alice]: ");
+
+        let answer = ask(
+            &mut " bob \n".as_bytes(),
+            &mut Vec::new(),
+            "account",
+            Some("alice"),
+        );
+        assert_eq!(answer.unwrap(), "bob");
+        assert!(ask(&mut "".as_bytes(), &mut Vec::new(), "account", None).is_err());
+    }
+
+    #[test]
+    fn should_accept_yes() {
+        assert!(is_yes("Y"));
+        assert!(is_yes("yes"));
+        assert!(!is_yes(""));
+        assert!(!is_yes("no"));
+    }
+}
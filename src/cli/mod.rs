@@ -0,0 +1,297 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use eyre::{eyre, Report, Result};
+use log::LevelFilter;
+
+use self::output::{EntryRecord, StatRecord, UsageRecord};
+use crate::app::usage;
+use crate::config::{Config, Settings};
+use crate::request::{self, RequestError};
+
+pub mod login;
+pub mod output;
+pub use self::output::Output;
+
+const EXIT_STATUS: &str = "\
+Exit status:
+    0 success, 1 local error, 2 invalid arguments, 3 authentication or permission failure,
+    4 not found, 5 other client error, 6 server error, 7 connection failure";
+
+/// A terminal browser for the HDFS proxy, with subcommands mirroring `hdfs dfs` for scripting
+#[derive(Debug, Parser)]
+#[command(version, after_help = EXIT_STATUS)]
+pub struct Args {
+    /// The url to browse, the `base_url` of the config file by default
+    pub base_url: Option<String>,
+    /// The config file, `config.toml` in the user config directory by default
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    /// The profile of the config file to use
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// The file holding the account and the key, `access.toml` by default
+    #[arg(long, global = true)]
+    pub access_file: Option<PathBuf>,
+    /// Where files are saved, the working directory by default
+    #[arg(long, global = true)]
+    pub download_dir: Option<PathBuf>,
+    /// off, error, warn, info, debug (the default) or trace
+    #[arg(long, global = true, value_parser = parse_log_level)]
+    pub log_level: Option<LevelFilter>,
+    /// Milliseconds between two refreshes of the UI, 200 by default
+    #[arg(long, global = true)]
+    pub tick_rate: Option<u64>,
+    /// The format of the ls, stat and du results
+    #[arg(short, long, global = true, value_enum, default_value_t = Output::Table)]
+    pub output: Output,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Args {
+    /// The settings from the options, then the profile, then the config file
+    pub fn settings(&self) -> Result<Settings> {
+        let config = match &self.config {
+            Some(path) => Config::load(path)?,
+            None => Config::load_default()?,
+        };
+        let mut settings = config.settings(self.profile.as_deref())?;
+        if let Some(base_url) = &self.base_url {
+            settings.connection.base_url = Some(base_url.clone());
+        }
+        if let Some(access_file) = &self.access_file {
+            settings.connection.access_file = access_file.clone();
+            settings.connection.account = None;
+            settings.connection.key = None;
+            settings.connection.key_command = None;
+        }
+        if let Some(download_dir) = &self.download_dir {
+            settings.download_dir = download_dir.clone();
+        }
+        if let Some(log_level) = self.log_level {
+            settings.log_level = Some(log_level);
+        }
+        if let Some(tick_rate) = self.tick_rate {
+            settings.tick_rate = Duration::from_millis(tick_rate);
+        }
+        Ok(settings)
+    }
+}
+
+fn parse_log_level(level: &str) -> Result<LevelFilter, String> {
+    level
+        .parse()
+        .map_err(|_| format!("invalid log level {:?}", level))
+}
+
+/// The exit status of a failed subcommand, from the request error when there is one
+pub fn exit_code(error: &Report) -> i32 {
+    match error.downcast_ref::<RequestError>() {
+        Some(RequestError::AuthFailed { .. } | RequestError::PermissionDenied { .. }) => 3,
+        Some(RequestError::NotFound { .. }) => 4,
+        Some(RequestError::Rejected { .. }) => 5,
+        Some(RequestError::Server { .. }) => 6,
+        Some(e) if e.is_unreachable() => 7,
+        _ => 1,
+    }
+}
+
+/// A subcommand run without the TUI
+#[derive(Clone, Debug, PartialEq, Eq, Subcommand)]
+pub enum Command {
+    /// List a directory
+    Ls { url: String },
+    /// Print a file
+    Cat { url: String },
+    /// Download a file, in the download directory by default
+    Get { url: String, local: Option<PathBuf> },
+    /// Upload a file, in the directory when the url ends with a '/'
+    Put { local: PathBuf, url: String },
+    /// Delete a file, or a directory with -r
+    Rm {
+        #[arg(short, long)]
+        recursive: bool,
+        url: String,
+    },
+    /// Create a directory and its missing parents
+    Mkdir { url: String },
+    /// Print the permission, owner, group and replication
+    Stat { url: String },
+    /// Print the size of each entry of a directory, then the total
+    Du { url: String },
+}
+
+impl Command {
+    pub async fn run(self, output: Output, download_dir: &Path) -> Result<()> {
+        let mut out = std::io::stdout().lock();
+        match self {
+            Command::Ls { url } => {
+                let url = dir_url(&url);
+                let items = request::get_item_list(&url).await?;
+                let records = items
+                    .iter()
+                    .map(|item| EntryRecord::new(&url, item))
+                    .collect::<Vec<_>>();
+                output.write(&mut out, &records)?;
+            }
+            Command::Cat { url } => {
+                let mut res = request::get_file_stream(&url).await?;
+                while let Some(chunk) = res.chunk().await.map_err(RequestError::from)? {
+                    out.write_all(&chunk)?;
+                }
+            }
+            Command::Get { url, local } => {
+                let dest = match local {
+                    Some(local) if local.is_dir() => local.join(file_name(&url)?),
+                    Some(local) => local,
+                    None => download_dir.join(file_name(&url)?),
+                };
+                request::download_file(&url, &dest).await?;
+            }
+            Command::Put { local, url } => {
+                let url = if url.ends_with('/') {
+                    let name = local
+                        .file_name()
+                        .ok_or_else(|| eyre!("{} is not a file", local.display()))?;
+                    format!("{}{}", url, name.to_string_lossy())
+                } else {
+                    url
+                };
+                request::upload_file(&local, &url).await?;
+            }
+            Command::Rm { url, recursive } => request::delete(&url, recursive).await?,
+            Command::Mkdir { url } => request::mkdirs(&dir_url(&url)).await?,
+            Command::Stat { url } => {
+                let status = request::get_file_status(&url).await?;
+                output.write_one(&mut out, &StatRecord::new(&url, status))?;
+            }
+            Command::Du { url } => {
+                let url = dir_url(&url);
+                let root = usage::scan(url.clone(), String::new()).await?;
+                let records = root
+                    .children
+                    .iter()
+                    .map(|child| UsageRecord::new(&url, child))
+                    .chain(Some(UsageRecord {
+                        url: url.clone(),
+                        kind: "directory",
+                        size: root.size,
+                    }))
+                    .collect::<Vec<_>>();
+                // the total comes last
+                output.write(&mut out, &records)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Prints the log records on the standard error, as the subcommands have no log panel
+struct StderrLogger;
+
+impl log::Log for StderrLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            let level = match record.level() {
+                log::Level::Warn => "warning".to_string(),
+                level => level.as_str().to_lowercase(),
+            };
+            eprintln!("{}: {}", level, record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Log to the standard error, only the warnings and errors unless `--log-level` is given
+pub fn init_logger(level: Option<LevelFilter>) {
+    static LOGGER: StderrLogger = StderrLogger;
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level.unwrap_or(LevelFilter::Warn));
+    }
+}
+
+/// Directories are listed with a trailing '/'
+fn dir_url(url: &str) -> String {
+    if url.ends_with('/') {
+        url.to_string()
+    } else {
+        format!("{}/", url)
+    }
+}
+
+fn file_name(url: &str) -> Result<&str> {
+    match url.rsplit('/').next() {
+        Some(name) if !name.is_empty() => Ok(name),
+        _ => Err(eyre!("{} is not a file", url)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> clap::error::Result<Args> {
+        Args::try_parse_from(Some("hdfs_proxy_tui").iter().chain(args))
+    }
+
+    #[test]
+    fn should_parse_subcommands() {
+        assert_eq!(
+            parse(&["ls", "http://host/data"]).unwrap().command,
+            Some(Command::Ls {
+                url: "http://host/data".to_string()
+            })
+        );
+        assert_eq!(
+            parse(&["rm", "-r", "http://host/data/"]).unwrap().command,
+            Some(Command::Rm {
+                url: "http://host/data/".to_string(),
+                recursive: true
+            })
+        );
+        assert_eq!(
+            parse(&["get", "http://host/a.csv", "/tmp"])
+                .unwrap()
+                .command,
+            Some(Command::Get {
+                url: "http://host/a.csv".to_string(),
+                local: Some(PathBuf::from("/tmp"))
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_arguments() {
+        assert!(parse(&["put", "a.csv"]).is_err());
+        assert!(parse(&["--tick-rate", "fast"]).is_err());
+        let args = parse(&["http://host/"]).unwrap();
+        assert_eq!(args.command, None);
+        assert_eq!(args.base_url.as_deref(), Some("http://host/"));
+        assert_eq!(parse(&[]).unwrap().base_url, None);
+    }
+
+    #[test]
+    fn should_parse_options() {
+        let args = parse(&["--output", "ndjson", "ls", "http://host/"]).unwrap();
+        assert_eq!(args.output, Output::Ndjson);
+        assert!(parse(&["stat", "http://host/a.csv", "-o", "xml"]).is_err());
+
+        let args = parse(&["--log-level", "warn", "--tick-rate", "50", "http://host/"]).unwrap();
+        assert_eq!(args.log_level, Some(LevelFilter::Warn));
+        assert_eq!(args.tick_rate, Some(50));
+    }
+
+    #[test]
+    fn should_extract_file_name() {
+        assert_eq!(file_name("http://host/data/a.csv").unwrap(), "a.csv");
+        assert!(file_name("http://host/data/").is_err());
+    }
+}
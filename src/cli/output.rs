@@ -0,0 +1,294 @@
+use std::io::Write;
+
+use clap::ValueEnum;
+use serde::Serialize;
+
+use crate::app::properties::FileStatus;
+use crate::app::state::Item;
+use crate::app::usage::UsageNode;
+
+/// How the results are printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Output {
+    /// Aligned columns, for humans
+    Table,
+    /// A JSON array, or an object for a single result
+    Json,
+    /// One JSON object per line
+    Ndjson,
+    /// Comma separated values, with a header line
+    Csv,
+}
+
+/// A printed result, the field names are part of the scripting interface
+pub trait Record: Serialize {
+    const FIELDS: &'static [&'static str];
+
+    /// The values in the `FIELDS` order, for the table and csv outputs
+    fn values(&self) -> Vec<String>;
+}
+
+/// An entry of a directory listing
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct EntryRecord {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// `None` for directories
+    pub size: Option<u64>,
+    pub url: String,
+}
+
+impl EntryRecord {
+    pub fn new(dir_url: &str, item: &Item) -> Self {
+        let is_dir = item.size == -1;
+        Self {
+            name: item.name.clone(),
+            kind: if is_dir { "directory" } else { "file" },
+            size: if is_dir { None } else { Some(item.size as u64) },
+            url: if is_dir {
+                format!("{}{}/", dir_url, item.name)
+            } else {
+                format!("{}{}", dir_url, item.name)
+            },
+        }
+    }
+}
+
+impl Record for EntryRecord {
+    const FIELDS: &'static [&'static str] = &["name", "type", "size", "url"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.name.clone(),
+            self.kind.to_string(),
+            self.size.map(|size| size.to_string()).unwrap_or_default(),
+            self.url.clone(),
+        ]
+    }
+}
+
+/// The properties of an entry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct StatRecord {
+    pub url: String,
+    pub permission: String,
+    pub owner: String,
+    pub group: String,
+    pub replication: u16,
+}
+
+impl StatRecord {
+    pub fn new(url: &str, status: FileStatus) -> Self {
+        Self {
+            url: url.to_string(),
+            permission: status.permission,
+            owner: status.owner,
+            group: status.group,
+            replication: status.replication,
+        }
+    }
+}
+
+impl Record for StatRecord {
+    const FIELDS: &'static [&'static str] = &["url", "permission", "owner", "group", "replication"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.url.clone(),
+            self.permission.clone(),
+            self.owner.clone(),
+            self.group.clone(),
+            self.replication.to_string(),
+        ]
+    }
+}
+
+/// The aggregated size of an entry
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct UsageRecord {
+    pub url: String,
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    pub size: u64,
+}
+
+impl UsageRecord {
+    pub fn new(dir_url: &str, node: &UsageNode) -> Self {
+        Self {
+            url: if node.is_dir {
+                format!("{}{}/", dir_url, node.name)
+            } else {
+                format!("{}{}", dir_url, node.name)
+            },
+            kind: if node.is_dir { "directory" } else { "file" },
+            size: node.size,
+        }
+    }
+}
+
+impl Record for UsageRecord {
+    const FIELDS: &'static [&'static str] = &["url", "type", "size"];
+
+    fn values(&self) -> Vec<String> {
+        vec![
+            self.url.clone(),
+            self.kind.to_string(),
+            self.size.to_string(),
+        ]
+    }
+}
+
+impl Output {
+    /// Print a list of records
+    pub fn write<R: Record, W: Write>(&self, out: &mut W, records: &[R]) -> eyre::Result<()> {
+        match self {
+            Output::Table => write_table(out, R::FIELDS, records.iter().map(R::values).collect())?,
+            Output::Json => writeln!(out, "{}", serde_json::to_string_pretty(records)?)?,
+            Output::Ndjson => {
+                for record in records {
+                    writeln!(out, "{}", serde_json::to_string(record)?)?;
+                }
+            }
+            Output::Csv => {
+                writeln!(out, "{}", R::FIELDS.join(","))?;
+                for record in records {
+                    let values = record.values();
+                    let values = values
+                        .iter()
+                        .map(|value| csv_field(value))
+                        .collect::<Vec<_>>();
+                    writeln!(out, "{}", values.join(","))?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Print a single record, as an object in JSON and as field/value rows in a table
+    pub fn write_one<R: Record, W: Write>(&self, out: &mut W, record: &R) -> eyre::Result<()> {
+        match self {
+            Output::Table => {
+                let rows = R::FIELDS
+                    .iter()
+                    .zip(record.values())
+                    .map(|(field, value)| vec![format!("{}:", field), value])
+                    .collect();
+                write_table(out, &[], rows)?;
+            }
+            Output::Json => writeln!(out, "{}", serde_json::to_string_pretty(record)?)?,
+            _ => self.write(out, std::slice::from_ref(record))?,
+        }
+        Ok(())
+    }
+}
+
+/// Columns padded to their widest value, the header is skipped when empty
+fn write_table<W: Write>(
+    out: &mut W,
+    header: &[&str],
+    rows: Vec<Vec<String>>,
+) -> std::io::Result<()> {
+    let header = header
+        .iter()
+        .map(|field| field.to_uppercase())
+        .collect::<Vec<_>>();
+    let mut widths = header.iter().map(String::len).collect::<Vec<_>>();
+    for row in rows.iter() {
+        widths.resize(widths.len().max(row.len()), 0);
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    let lines = Some(header)
+        .filter(|header| !header.is_empty())
+        .into_iter()
+        .chain(rows);
+    for line in lines {
+        let cells = line
+            .iter()
+            .zip(widths.iter())
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+/// Quote a csv field when needed (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<EntryRecord> {
+        let dir = "http://host/data/";
+        vec![
+            EntryRecord::new(
+                dir,
+                &Item {
+                    name: "logs".to_string(),
+                    size: -1,
+                },
+            ),
+            EntryRecord::new(
+                dir,
+                &Item {
+                    name: "a,b.csv".to_string(),
+                    size: 42,
+                },
+            ),
+        ]
+    }
+
+    fn written<F: FnOnce(&mut Vec<u8>) -> eyre::Result<()>>(write: F) -> String {
+        let mut out = Vec::new();
+        write(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn should_write_ndjson_with_stable_fields() {
+        let out = written(|out| Output::Ndjson.write(out, &entries()));
+        assert_eq!(
+            out,
+            "{\"name\":\"logs\",\"type\":\"directory\",\"size\":null,\"url\":\"http://host/data/logs/\"}\n\
+             {\"name\":\"a,b.csv\",\"type\":\"file\",\"size\":42,\"url\":\"http://host/data/a,b.csv\"}\n"
+        );
+    }
+
+    #[test]
+    fn should_write_csv() {
+        let out = written(|out| Output::Csv.write(out, &entries()));
+        assert_eq!(
+            out,
+            "name,type,size,url\n\
+             logs,directory,,http://host/data/logs/\n\
+             \"a,b.csv\",file,42,\"http://host/data/a,b.csv\"\n"
+        );
+    }
+
+    #[test]
+    fn should_write_table() {
+        let out = written(|out| Output::Table.write(out, &entries()));
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], "NAME     TYPE       SIZE  URL");
+        assert_eq!(
+            lines[2],
+            "a,b.csv  file       42    http://host/data/a,b.csv"
+        );
+    }
+
+    #[test]
+    fn should_parse_output() {
+        assert_eq!(Output::from_str("ndjson", false), Ok(Output::Ndjson));
+        assert!(Output::from_str("xml", false).is_err());
+    }
+}
@@ -0,0 +1,417 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eyre::{eyre, Result, WrapErr};
+use log::LevelFilter;
+use serde::Deserialize;
+
+use crate::request::credentials::{self, AuthScheme, Credentials};
+use crate::request::http::Http;
+use crate::request::negotiate::Negotiate;
+use crate::request::retry::Retry;
+use crate::request::signer::Signer;
+
+/// The directory holding the user configuration files,
+/// e.g. `~/.config/hdfs-proxy-tui` on Linux.
+///
+/// Falls back to the working directory when the platform has no config directory.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .map(|dir| dir.join("hdfs-proxy-tui"))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Where the bookmarks are stored
+pub fn bookmarks_path() -> PathBuf {
+    config_dir().join("bookmarks.toml")
+}
+
+/// Where the commands to open files with, per extension, are configured
+pub fn open_with_path() -> PathBuf {
+    config_dir().join("open_with.toml")
+}
+
+/// The default config file
+pub fn config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// The name of the connection made of the top level values of the config file
+pub const DEFAULT: &str = "default";
+
+/// The config file, every value can be overridden by a profile then by the command line.
+/// Each profile with a base url is a connection that can be switched to from the TUI.
+///
+/// ```toml
+/// base_url = "http://localhost:7878/user/hive/"
+/// log_level = "info"
+///
+/// [profile.prod]
+/// base_url = "http://proxy.prod:7878/"
+/// access_file = "/etc/hdfs-proxy-tui/prod.toml"
+///
+/// [profile.sandbox]
+/// base_url = "http://sandbox:7878/"
+/// auth = "none"
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(flatten)]
+    pub defaults: Profile,
+    pub log_level: Option<String>,
+    /// In milliseconds
+    pub tick_rate: Option<u64>,
+    #[serde(default)]
+    pub profile: HashMap<String, Profile>,
+}
+
+/// The values a profile can set
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct Profile {
+    pub base_url: Option<String>,
+    pub access_file: Option<PathBuf>,
+    /// The account and the key, instead of the access file
+    pub account: Option<String>,
+    pub key: Option<String>,
+    /// A command printing the key, e.g. `pass show hdfs/prod`
+    pub key_command: Option<String>,
+    pub auth: Option<AuthScheme>,
+    /// How the requests are signed, see `Signer`
+    pub signer: Option<Signer>,
+    /// The Kerberos token helper, see `Negotiate`
+    pub negotiate: Option<Negotiate>,
+    /// The timeouts, TLS and proxy options, see `Http`
+    pub http: Option<Http>,
+    /// How the idempotent requests are retried, see `Retry`
+    pub retry: Option<Retry>,
+    pub download_dir: Option<PathBuf>,
+}
+
+/// A proxy to connect to, with the credentials to sign the requests with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Connection {
+    /// The profile name, `default` for the top level values
+    pub name: String,
+    pub base_url: Option<String>,
+    pub access_file: PathBuf,
+    pub account: Option<String>,
+    pub key: Option<String>,
+    pub key_command: Option<String>,
+    pub auth: AuthScheme,
+    pub signer: Signer,
+    pub negotiate: Option<Negotiate>,
+    pub http: Http,
+    pub retry: Retry,
+}
+
+impl Connection {
+    /// The HTTP client to send the requests with
+    pub fn client(&self) -> Result<reqwest::Client> {
+        self.http.client()
+    }
+
+    /// The account and the key, each one from the first source setting it:
+    /// the profile (`key_command` first), the environment, then the access file
+    pub fn credentials(&self) -> Result<Credentials> {
+        let account = self
+            .account
+            .clone()
+            .or_else(|| credentials::from_env(credentials::ACCOUNT_VAR));
+        let base = Credentials {
+            signer: self.signer.clone(),
+            ..Credentials::default()
+        };
+        match self.auth {
+            AuthScheme::None => {
+                return Ok(Credentials {
+                    account: account.unwrap_or_default(),
+                    auth: AuthScheme::None,
+                    ..base
+                });
+            }
+            // the Kerberos principal is usually the local user
+            AuthScheme::Negotiate => {
+                let negotiate = self
+                    .negotiate
+                    .clone()
+                    .ok_or_else(|| eyre!("the {} profile has no [negotiate] command", self.name))?;
+                return Ok(Credentials {
+                    account: account
+                        .or_else(|| credentials::from_env("USER"))
+                        .or_else(|| credentials::from_env("USERNAME"))
+                        .unwrap_or_default(),
+                    auth: AuthScheme::Negotiate,
+                    negotiate: Some(negotiate),
+                    ..base
+                });
+            }
+            AuthScheme::Token => {}
+        }
+        let key = match &self.key_command {
+            Some(command) => Some(credentials::run_key_command(command)?),
+            None => self
+                .key
+                .clone()
+                .or_else(|| credentials::from_env(credentials::KEY_VAR)),
+        };
+        match (account, key) {
+            (Some(account), Some(key)) => Ok(Credentials {
+                account,
+                key,
+                ..base
+            }),
+            (account, key) => {
+                let file = Credentials::from_access_file(&self.access_file, self.auth)?;
+                Ok(Credentials {
+                    account: account.unwrap_or(file.account),
+                    key: key.unwrap_or(file.key),
+                    ..base
+                })
+            }
+        }
+    }
+}
+
+/// The resolved settings of a run
+#[derive(Clone, Debug)]
+pub struct Settings {
+    /// The connection to start with
+    pub connection: Connection,
+    /// The connections of the config file having a base url, to switch to
+    pub connections: Vec<Connection>,
+    pub download_dir: PathBuf,
+    /// `None` when neither the config file nor the command line sets it,
+    /// each mode has its own default
+    pub log_level: Option<LevelFilter>,
+    pub tick_rate: Duration,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("can not read the config file {}", path.display()))?;
+        toml::from_str(&content).wrap_err_with(|| format!("invalid config file {}", path.display()))
+    }
+
+    /// Load the default config file, which does not have to exist
+    pub fn load_default() -> Result<Self> {
+        let path = config_path();
+        if path.exists() {
+            Self::load(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// The settings with the values of `profile` over the top level ones
+    pub fn settings(&self, profile: Option<&str>) -> Result<Settings> {
+        let connection = match profile {
+            Some(name) => self.connection(name)?,
+            None => self.connection(DEFAULT)?,
+        };
+        let download_dir = profile
+            .and_then(|name| self.profile.get(name))
+            .and_then(|profile| profile.download_dir.clone())
+            .or_else(|| self.defaults.download_dir.clone())
+            .unwrap_or_else(|| PathBuf::from("."));
+        let log_level = match &self.log_level {
+            Some(level) => Some(
+                level
+                    .parse()
+                    .map_err(|_| eyre!("invalid log level {:?} in the config file", level))?,
+            ),
+            None => None,
+        };
+        Ok(Settings {
+            connection,
+            connections: self.connections(),
+            download_dir,
+            log_level,
+            tick_rate: Duration::from_millis(self.tick_rate.unwrap_or(200)),
+        })
+    }
+
+    /// The connection of a profile, `default` being the top level values
+    pub fn connection(&self, name: &str) -> Result<Connection> {
+        let profile = if name == DEFAULT {
+            &self.defaults
+        } else {
+            self.profile
+                .get(name)
+                .ok_or_else(|| eyre!("no profile {:?} in the config file", name))?
+        };
+        let defaults = &self.defaults;
+        Ok(Connection {
+            name: name.to_string(),
+            base_url: profile
+                .base_url
+                .clone()
+                .or_else(|| defaults.base_url.clone()),
+            access_file: profile
+                .access_file
+                .clone()
+                .or_else(|| defaults.access_file.clone())
+                .unwrap_or_else(|| PathBuf::from("access.toml")),
+            account: profile.account.clone().or_else(|| defaults.account.clone()),
+            key: profile.key.clone().or_else(|| defaults.key.clone()),
+            key_command: profile
+                .key_command
+                .clone()
+                .or_else(|| defaults.key_command.clone()),
+            auth: profile.auth.or(defaults.auth).unwrap_or_default(),
+            signer: profile
+                .signer
+                .clone()
+                .or_else(|| defaults.signer.clone())
+                .unwrap_or_default(),
+            negotiate: profile
+                .negotiate
+                .clone()
+                .or_else(|| defaults.negotiate.clone()),
+            http: profile
+                .http
+                .clone()
+                .or_else(|| defaults.http.clone())
+                .unwrap_or_default(),
+            retry: profile
+                .retry
+                .clone()
+                .or_else(|| defaults.retry.clone())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// The connections having a base url, the top level one first then by name
+    pub fn connections(&self) -> Vec<Connection> {
+        let mut names = self.profile.keys().collect::<Vec<_>>();
+        names.sort();
+        let default = Some(DEFAULT).filter(|_| self.defaults.base_url.is_some());
+        default
+            .into_iter()
+            .chain(names.into_iter().map(String::as_str))
+            .filter_map(|name| self.connection(name).ok())
+            .filter(|connection| connection.base_url.is_some())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::signer::Algorithm;
+
+    const CONFIG: &str = r#"
+        base_url = "http://localhost:7878/"
+        download_dir = "/tmp"
+        log_level = "info"
+        tick_rate = 100
+
+        [profile.prod]
+        base_url = "http://proxy.prod:7878/"
+        access_file = "prod.toml"
+
+        [profile.sandbox]
+        base_url = "http://sandbox:7878/"
+        account = "alice"
+        auth = "none"
+
+        [profile.secure]
+        base_url = "https://proxy.secure:7878/"
+        auth = "negotiate"
+        account = "alice"
+
+        [profile.secure.negotiate]
+        command = "spnego-token HTTP@{host}"
+        keytab = "/etc/alice.keytab"
+
+        [profile.prod.http]
+        timeout = 60
+        insecure = true
+
+        [profile.prod.retry]
+        max_attempts = 5
+
+        [profile.prod.signer]
+        algorithm = "hmac-sha256"
+        include_query = true
+    "#;
+
+    #[test]
+    fn should_use_defaults_without_config() {
+        let settings = Config::default().settings(None).unwrap();
+        assert_eq!(settings.connection.base_url, None);
+        assert_eq!(
+            settings.connection.access_file,
+            PathBuf::from("access.toml")
+        );
+        assert!(settings.connections.is_empty());
+        assert_eq!(settings.log_level, None);
+        assert_eq!(settings.tick_rate, Duration::from_millis(200));
+    }
+
+    #[test]
+    fn should_override_with_profile() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let settings = config.settings(Some("prod")).unwrap();
+        assert_eq!(
+            settings.connection.base_url.as_deref(),
+            Some("http://proxy.prod:7878/")
+        );
+        assert_eq!(settings.connection.access_file, PathBuf::from("prod.toml"));
+        assert_eq!(settings.connection.signer.algorithm, Algorithm::HmacSha256);
+        assert_eq!(settings.connection.signer.header, "auth-token");
+        assert_eq!(settings.connection.http.timeout, Some(60));
+        assert_eq!(settings.connection.retry.max_attempts, 5);
+        assert!(settings.connection.client().is_ok());
+        assert_eq!(settings.download_dir, PathBuf::from("/tmp"));
+        assert_eq!(settings.log_level, Some(LevelFilter::Info));
+        assert_eq!(settings.tick_rate, Duration::from_millis(100));
+
+        let settings = config.settings(None).unwrap();
+        assert_eq!(
+            settings.connection.base_url.as_deref(),
+            Some("http://localhost:7878/")
+        );
+        assert!(config.settings(Some("staging")).is_err());
+    }
+
+    #[test]
+    fn should_list_connections() {
+        let config: Config = toml::from_str(CONFIG).unwrap();
+        let names = config
+            .connections()
+            .into_iter()
+            .map(|connection| connection.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["default", "prod", "sandbox", "secure"]);
+
+        let sandbox = config.connection("sandbox").unwrap();
+        let credentials = sandbox.credentials().unwrap();
+        assert_eq!(credentials.account, "alice");
+        assert_eq!(credentials.auth, AuthScheme::None);
+
+        let secure = config.connection("secure").unwrap().credentials().unwrap();
+        assert_eq!(secure.auth, AuthScheme::Negotiate);
+        assert_eq!(
+            secure.negotiate.unwrap().keytab,
+            Some(PathBuf::from("/etc/alice.keytab"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_prefer_key_command() {
+        let config: Config = toml::from_str(
+            r#"
+            account = "alice"
+            key = "plain"
+            key_command = "echo s3cret"
+            "#,
+        )
+        .unwrap();
+        let credentials = config.connection(DEFAULT).unwrap().credentials().unwrap();
+        assert_eq!(credentials.account, "alice");
+        assert_eq!(credentials.key, "s3cret");
+    }
+}
@@ -15,6 +15,8 @@ pub struct Events {
     _tx: tokio::sync::mpsc::Sender<InputEvent>,
     // To stop the loop
     stop_capture: Arc<AtomicBool>,
+    // Held while reading the terminal, or while an external program owns it
+    capture: Arc<tokio::sync::Mutex<()>>,
 }
 
 impl Events {
@@ -22,18 +24,27 @@ impl Events {
     pub fn new(tick_rate: Duration) -> Events {
         let (tx, rx) = tokio::sync::mpsc::channel(100);
         let stop_capture = Arc::new(AtomicBool::new(false));
+        let capture = Arc::new(tokio::sync::Mutex::new(()));
 
         let event_tx = tx.clone();
         let event_stop_capture = stop_capture.clone();
+        let event_capture = capture.clone();
         tokio::spawn(async move {
             loop {
                 // poll for tick rate duration, if no event, sent tick event.
-                if crossterm::event::poll(tick_rate).unwrap() {
-                    if let crossterm::event::Event::Key(key) = crossterm::event::read().unwrap() {
-                        let key = Key::from(key);
-                        if let Err(err) = event_tx.send(InputEvent::Input(key)).await {
-                            error!("Oops!, {}", err);
-                        }
+                let event = {
+                    // the terminal is only read while it is not suspended
+                    let _capture = event_capture.lock().await;
+                    if crossterm::event::poll(tick_rate).unwrap() {
+                        Some(crossterm::event::read().unwrap())
+                    } else {
+                        None
+                    }
+                };
+                if let Some(crossterm::event::Event::Key(key)) = event {
+                    let key = Key::from(key);
+                    if let Err(err) = event_tx.send(InputEvent::Input(key)).await {
+                        error!("Oops!, {}", err);
                     }
                 }
                 if let Err(err) = event_tx.send(InputEvent::Tick).await {
@@ -49,6 +60,7 @@ impl Events {
             rx,
             _tx: tx,
             stop_capture,
+            capture,
         }
     }
 
@@ -57,6 +69,12 @@ impl Events {
         self.rx.recv().await.unwrap_or(InputEvent::Tick)
     }
 
+    /// Stop reading the terminal until the guard is dropped, so an external
+    /// program gets the keys
+    pub async fn suspend(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.capture.lock().await
+    }
+
     /// Close
     pub fn close(&mut self) {
         self.stop_capture.store(true, Ordering::Relaxed)
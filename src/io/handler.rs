@@ -1,11 +1,19 @@
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 
-use eyre::Result;
-use log::{error, info};
+use eyre::{eyre, Result};
+use log::{error, info, warn};
 
-use super::IoEvent;
+use super::{BatchOp, IoEvent};
+use crate::app::external;
+use crate::app::prompt::split_parent;
+use crate::app::properties::PropertyChange;
+use crate::app::state::{AppState, Item};
+use crate::app::usage;
 use crate::app::App;
+use crate::config::Connection;
+use crate::request;
 
 /// In the IO thread, we handle IO event without blocking the UI thread
 pub struct IoAsyncHandler {
@@ -19,33 +27,74 @@ impl IoAsyncHandler {
 
     /// We could be async here
     pub async fn handle_io_event(&mut self, io_event: IoEvent) {
-        let result = match io_event {
-            IoEvent::Initialize(init_url) => self.do_initialize(init_url).await,
-            IoEvent::Sleep(duration) => self.do_sleep(duration).await,
-            IoEvent::StepIn => self.do_step_in().await,
-            IoEvent::StepOut => self.do_step_out().await,
-            IoEvent::MoveUp => self.do_move_up().await,
-            IoEvent::MoveDown => self.do_move_down().await,
-        };
+        // The requests of the IO task are retried with the policy of the connection
+        let result = request::with_retries(async {
+            match io_event {
+                IoEvent::Initialize(init_url) => self.do_initialize(init_url).await,
+                IoEvent::Sleep(duration) => self.do_sleep(duration).await,
+                IoEvent::StepIn => self.do_step_in().await,
+                IoEvent::StepOut => self.do_step_out().await,
+                IoEvent::MoveUp => self.do_move_up().await,
+                IoEvent::MoveDown => self.do_move_down().await,
+                IoEvent::ScanUsage(root_url) => self.do_scan_usage(root_url).await,
+                IoEvent::Batch(ops) => self.do_batch(ops).await,
+                IoEvent::Mkdir(url) => self.do_mkdir(url).await,
+                IoEvent::Rename(moves) => self.do_rename(moves).await,
+                IoEvent::LoadProperties(url) => self.do_load_properties(url).await,
+                IoEvent::SetProperties(url, changes) => self.do_set_properties(url, changes).await,
+                IoEvent::Edit(url) => self.do_edit(url).await,
+                IoEvent::Open(url, filter) => self.do_open(url, filter).await,
+                IoEvent::UploadEdited { url, path, original } => self.do_upload_edited(url, path, original).await,
+                IoEvent::Connect(connection) => self.do_connect(*connection).await,
+            }
+        })
+        .await;
 
+        let mut app = self.app.lock().await;
         if let Err(err) = result {
-            error!("Oops, something wrong happen: {:?}", err);
+            app.show_failures(vec![format!("{:#}", err)]);
         }
-
-        let mut app = self.app.lock().await;
         app.loaded();
     }
 
     /// We use dummy implementation here, just wait 1s
     async fn do_initialize(&mut self, base_url:String) -> Result<()> {
         info!("🚀 Initialize the application");
-        let mut app = self.app.lock().await;
         tokio::time::sleep(Duration::from_secs(1)).await;
-        app.initialized(base_url).await; // we could update the app state
+        // Load without the app lock, the UI keeps drawing while the requests are retried
+        let state = AppState::initialized(base_url).await;
+        self.app.lock().await.initialized(state);
         info!("👍 Application initialized");
         Ok(())
     }
 
+    /// Sign the next requests with the credentials of `connection` and browse its base url,
+    /// the current connection is kept when its credentials can not be loaded or its base url
+    /// can not be browsed
+    async fn do_connect(&mut self, connection: Connection) -> Result<()> {
+        let base_url = connection
+            .base_url
+            .clone()
+            .ok_or_else(|| eyre!("the connection {} has no base url", connection.name))?;
+        let credentials = connection.credentials()?;
+        let client = connection.client()?;
+        let previous = request::session();
+        request::set_credentials(credentials);
+        request::set_client(client);
+        request::set_retry(connection.retry.clone());
+        let state = AppState::initialized(base_url.clone()).await;
+        let mut app = self.app.lock().await;
+        if state.is_ok() {
+            info!("🔌 connected to {} ({})", connection.name, base_url);
+            app.connected(connection.name);
+        } else {
+            warn!("🔌 can not connect to {}, keeping the current connection", connection.name);
+            request::restore(previous);
+        }
+        app.initialized(state);
+        Ok(())
+    }
+
     /// Just take a little break
     async fn do_sleep(&mut self, duration: Duration) -> Result<()> {
         info!("😴 Go sleeping for {:?}...", duration);
@@ -76,4 +125,171 @@ impl IoAsyncHandler {
         info!("👇 move down");
         Ok(())
     }
+
+    /// The scan may be long, so the app is only locked to store the result
+    async fn do_scan_usage(&mut self, root_url: String) -> Result<()> {
+        info!("📊 scanning {}...", root_url);
+        let root = match usage::scan(root_url.clone(), String::new()).await {
+            Ok(root) => root,
+            Err(e) => {
+                self.app.lock().await.usage_scan_failed(&root_url, e.to_string());
+                return Err(e.into());
+            }
+        };
+        info!("📊 scan of {} done: {}", root_url, usage::human_size(root.size));
+        let mut app = self.app.lock().await;
+        app.usage_scanned(&root_url, root);
+        Ok(())
+    }
+
+    /// Run the operations one after the other, the progress is reported to the app
+    async fn do_batch(&mut self, ops: Vec<BatchOp>) -> Result<()> {
+        let total = ops.len();
+        let mut failures = Vec::new();
+        let mut changed_dirs = Vec::new();
+        for (index, op) in ops.into_iter().enumerate() {
+            self.app.lock().await.batch_progress(index, total);
+            info!("📦 [{}/{}] {}", index + 1, total, op);
+            if let Some(url) = op.remote_change() {
+                let (dir, _) = split_parent(url.trim_end_matches('/'));
+                if !changed_dirs.iter().any(|changed| changed == dir) {
+                    changed_dirs.push(dir.to_string());
+                }
+            }
+            let result = match &op {
+                BatchOp::Download { url, dest } => request::download_file(url, dest).await,
+                BatchOp::Upload { src, url } => request::upload_file(src, url).await,
+                BatchOp::Delete { url, recursive } => {
+                    request::delete(url, *recursive).await.map_err(Into::into)
+                }
+                BatchOp::Trash { url } => {
+                    request::move_to_trash(url)
+                        .await
+                        .map(|trash| info!("🗑️ {} moved to {}", url, trash))
+                        .map_err(Into::into)
+                }
+            };
+            if let Err(e) = result {
+                error!("📦 [{}/{}] failed to {}: {}", index + 1, total, op, e);
+                failures.push(format!("failed to {}: {}", op, e));
+            }
+        }
+        info!("📦 {} done, {} failed", total - failures.len(), failures.len());
+        let listings = Self::list_dirs(changed_dirs).await;
+        let mut app = self.app.lock().await;
+        app.batch_done(failures);
+        for (dir, items) in listings {
+            app.listing_loaded(&dir, items);
+        }
+        Ok(())
+    }
+
+    /// List the `dirs` again without locking the app, as the requests may be retried for a while
+    async fn list_dirs(dirs: Vec<String>) -> Vec<(String, Vec<Item>)> {
+        let mut listings = Vec::with_capacity(dirs.len());
+        for dir in dirs {
+            match request::get_item_list(&dir).await {
+                Ok(items) => listings.push((dir, items)),
+                Err(e) => warn!("📦 can not refresh {}: {}", dir, e),
+            }
+        }
+        listings
+    }
+
+    async fn do_mkdir(&mut self, url: String) -> Result<()> {
+        request::mkdirs(&url).await?;
+        info!("📁 {} created", url);
+        let mut app = self.app.lock().await;
+        app.entry_created(&url, -1);
+        Ok(())
+    }
+
+    /// Each entry is reflected in the listing as soon as it is moved
+    async fn do_rename(&mut self, moves: Vec<(String, String)>) -> Result<()> {
+        let mut failures = Vec::new();
+        for (from, to) in moves {
+            let (_, path) = request::split_origin(&to);
+            match request::rename(&from, path.trim_end_matches('/')).await {
+                Ok(()) => {
+                    info!("✏️ {} moved to {}", from, to);
+                    self.app.lock().await.entry_moved(&from, &to);
+                }
+                Err(e) => {
+                    error!("✏️ failed to move {} to {}: {}", from, to, e);
+                    failures.push(format!("failed to move {} to {}: {}", from, to, e));
+                }
+            }
+        }
+        self.app.lock().await.show_failures(failures);
+        Ok(())
+    }
+
+    async fn do_load_properties(&mut self, url: String) -> Result<()> {
+        let status = request::get_file_status(&url).await?;
+        let mut app = self.app.lock().await;
+        app.properties_loaded(url, status);
+        Ok(())
+    }
+
+    async fn do_set_properties(&mut self, url: String, changes: Vec<PropertyChange>) -> Result<()> {
+        let mut failures = Vec::new();
+        for change in changes {
+            match request::set_property(&url, &change.query()).await {
+                Ok(()) => info!("🔐 {} {}", change, url),
+                Err(e) => {
+                    error!("🔐 failed to {} {}: {}", change, url, e);
+                    failures.push(format!("failed to {} {}: {}", change, url, e));
+                }
+            }
+        }
+        self.app.lock().await.show_failures(failures);
+        Ok(())
+    }
+
+    async fn do_edit(&mut self, url: String) -> Result<()> {
+        let original = request::get_file_bytes(&url).await?;
+        let name = url.rsplit('/').next().unwrap_or_default();
+        let path = external::temp_path(name)?;
+        std::fs::write(&path, &original)?;
+        info!("🖊️ {} downloaded to {}", url, path.display());
+        let mut app = self.app.lock().await;
+        app.edit_ready(url, path, original);
+        Ok(())
+    }
+
+    /// Only the response is awaited, its content is streamed to the program
+    async fn do_open(&mut self, url: String, filter: Option<String>) -> Result<()> {
+        let content = request::get_file_stream(&url).await?;
+        let mut app = self.app.lock().await;
+        app.open_ready(content, filter);
+        Ok(())
+    }
+
+    /// The file is not uploaded when it was modified remotely while it was edited
+    async fn do_upload_edited(&mut self, url: String, path: PathBuf, original: Vec<u8>) -> Result<()> {
+        let edited = std::fs::read(&path)?;
+        if edited == original {
+            info!("🖊️ {} unchanged", url);
+            std::fs::remove_file(&path)?;
+            return Ok(());
+        }
+        let remote = request::get_file_bytes(&url).await?;
+        if remote != original {
+            return Err(eyre!(
+                "{} was modified remotely while being edited, not uploaded, your version is kept in {}",
+                url,
+                path.display()
+            ));
+        }
+        request::put_file(&url, edited).await?;
+        std::fs::remove_file(&path)?;
+        info!("🖊️ {} saved", url);
+        let (dir, _) = split_parent(&url);
+        let listings = Self::list_dirs(vec![dir.to_string()]).await;
+        let mut app = self.app.lock().await;
+        for (dir, items) in listings {
+            app.listing_loaded(&dir, items);
+        }
+        Ok(())
+    }
 }
@@ -1,5 +1,10 @@
+use std::fmt::{self, Display};
+use std::path::PathBuf;
 use std::time::Duration;
 
+use crate::app::properties::PropertyChange;
+use crate::config::Connection;
+
 pub mod handler;
 // For this dummy application we only need two IO event
 #[derive(Debug, Clone)]
@@ -10,4 +15,51 @@ pub enum IoEvent {
     StepOut,  // Go back to the previous director
     MoveUp,          // Move up to the parent directory
     MoveDown,        // Move down to the child directory
+    ScanUsage(String), // Recursively compute the disk usage of a directory
+    Batch(Vec<BatchOp>), // Run the operations one after the other
+    Mkdir(String), // Create a remote directory
+    Rename(Vec<(String, String)>), // Rename or move remote entries, from url to url
+    LoadProperties(String), // Get the status of a remote entry for the properties dialog
+    SetProperties(String, Vec<PropertyChange>), // Apply the changes to a remote entry
+    Edit(String), // Download a remote file to edit it locally
+    Open(String, Option<String>), // Download a remote file to show it, through the command when given
+    UploadEdited { url: String, path: PathBuf, original: Vec<u8> }, // Upload the edited file if it changed
+    Connect(Box<Connection>), // Switch to another proxy and browse its base url
+}
+
+/// An operation of a batch
+#[derive(Debug, Clone)]
+pub enum BatchOp {
+    /// Copy a remote file to the local filesystem
+    Download { url: String, dest: PathBuf },
+    /// Copy a local file to the remote
+    Upload { src: PathBuf, url: String },
+    /// Delete a remote file or directory
+    Delete { url: String, recursive: bool },
+    /// Move a remote file or directory to the trash of the user
+    Trash { url: String },
+}
+
+impl BatchOp {
+    /// The remote entry changed by the operation, if any
+    pub fn remote_change(&self) -> Option<&str> {
+        match self {
+            BatchOp::Download { .. } => None,
+            BatchOp::Upload { url, .. } | BatchOp::Delete { url, .. } | BatchOp::Trash { url } => {
+                Some(url)
+            }
+        }
+    }
+}
+
+impl Display for BatchOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchOp::Download { url, dest } => write!(f, "download {} to {}", url, dest.display()),
+            BatchOp::Upload { src, url } => write!(f, "upload {} to {}", src.display(), url),
+            BatchOp::Delete { url, recursive: true } => write!(f, "delete {} recursively", url),
+            BatchOp::Delete { url, .. } => write!(f, "delete {}", url),
+            BatchOp::Trash { url } => write!(f, "move {} to the trash", url),
+        }
+    }
 }
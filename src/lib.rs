@@ -1,24 +1,29 @@
-use std::io::stdout;
+use std::io::{stdout, Stdout};
+use std::process::ExitStatus;
 use std::sync::Arc;
 use std::time::Duration;
 
+use app::external::External;
 use app::{App, AppReturn};
 use eyre::Result;
 use inputs::events::Events;
 use inputs::InputEvent;
 use io::IoEvent;
+use log::error;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
 use crate::app::ui;
 
 pub mod app;
+pub mod cli;
+pub mod config;
 pub mod inputs;
 pub mod io;
 pub mod request;
 extern crate crypto;
 
-pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>, base_url:String) -> Result<()> {
+pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>, base_url:String, tick_rate: Duration) -> Result<()> {
     // Configure Crossterm backend for tui
     let stdout = stdout();
     crossterm::terminal::enable_raw_mode()?;
@@ -28,7 +33,6 @@ pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>, base_url:String) -> Re
     terminal.hide_cursor()?;
 
     // User event handler
-    let tick_rate = Duration::from_millis(200);
     let mut events = Events::new(tick_rate);
 
     // Trigger state change from Init to Initialized
@@ -39,20 +43,30 @@ pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>, base_url:String) -> Re
     }
 
     loop {
-        let mut app = app.lock().await;
+        let external = {
+            let mut app = app.lock().await;
 
-        // Render
-        terminal.draw(|rect| ui::draw(rect, &mut app))?;
+            // Render
+            terminal.draw(|rect| ui::draw(rect, &mut app))?;
 
-        // Handle inputs
-        let result = match events.next().await {
-            InputEvent::Input(key) => app.do_action(key).await,
-            InputEvent::Tick => app.update_on_tick().await,
+            // Handle inputs
+            let result = match events.next().await {
+                InputEvent::Input(key) => app.do_action(key).await,
+                InputEvent::Tick => app.update_on_tick().await,
+            };
+            // Check if we should exit
+            if result == AppReturn::Exit {
+                events.close();
+                break;
+            }
+            app.take_external()
         };
-        // Check if we should exit
-        if result == AppReturn::Exit {
-            events.close();
-            break;
+
+        // Give the terminal to the external program asked by the app, the app is not locked
+        // meanwhile so the IO thread goes on
+        if let Some(external) = external {
+            let (external, status) = run_external(&mut terminal, &events, external).await?;
+            app.lock().await.external_done(external, status).await;
         }
     }
 
@@ -63,3 +77,50 @@ pub async fn start_ui(app: &Arc<tokio::sync::Mutex<App>>, base_url:String) -> Re
 
     Ok(())
 }
+
+/// Run `external` with the terminal restored to its normal mode, then take it back
+async fn run_external(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    events: &Events,
+    mut external: External,
+) -> Result<(External, std::io::Result<ExitStatus>)> {
+    let _suspended = events.suspend().await;
+    let _terminal = SuspendedTerminal::new(terminal)?;
+
+    // The program blocks until it exits, so it is waited for out of the runtime
+    let done = tokio::task::spawn_blocking(move || {
+        let status = external.run();
+        (external, status)
+    })
+    .await?;
+    Ok(done)
+}
+
+/// The terminal in its normal mode for an external program,
+/// it is taken back when dropped, whatever happened meanwhile
+struct SuspendedTerminal<'a> {
+    terminal: &'a mut Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl<'a> SuspendedTerminal<'a> {
+    fn new(terminal: &'a mut Terminal<CrosstermBackend<Stdout>>) -> Result<Self> {
+        // Built first, so that a failure below still takes the terminal back
+        let suspended = Self { terminal };
+        suspended.terminal.clear()?;
+        suspended.terminal.show_cursor()?;
+        crossterm::terminal::disable_raw_mode()?;
+        Ok(suspended)
+    }
+}
+
+impl Drop for SuspendedTerminal<'_> {
+    fn drop(&mut self) {
+        let restored = crossterm::terminal::enable_raw_mode()
+            .and_then(|_| self.terminal.hide_cursor())
+            // The program drew over the screen, everything has to be drawn again
+            .and_then(|_| self.terminal.clear());
+        if let Err(e) = restored {
+            error!("can not take the terminal back: {}", e);
+        }
+    }
+}
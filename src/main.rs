@@ -1,20 +1,45 @@
+use std::io::IsTerminal;
 use std::sync::Arc;
 
+use clap::{CommandFactory, Parser};
 use eyre::Result;
-use log::LevelFilter;
 use hdfs_proxy_tui::app::App;
+use hdfs_proxy_tui::cli::{self, login, Args};
+use hdfs_proxy_tui::config::Connection;
 use hdfs_proxy_tui::io::handler::IoAsyncHandler;
 use hdfs_proxy_tui::io::IoEvent;
+use hdfs_proxy_tui::request;
 use hdfs_proxy_tui::start_ui;
-use std::env;
+use log::LevelFilter;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let (sync_io_tx, mut sync_io_rx) = tokio::sync::mpsc::channel::<IoEvent>(100);
-    let args = env::args().collect::<Vec<String>>();
-    if args.len() < 2 {
-        println!("Usage: {} <base-url>", args[0]);
-        println!(r#"
+    let args = Args::parse();
+    let settings = match args.settings() {
+        Ok(settings) => settings,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            std::process::exit(2);
+        }
+    };
+
+    // Scripting mode, without the TUI
+    if let Some(command) = args.command {
+        cli::init_logger(settings.log_level);
+        use_connection(&settings.connection, false);
+        let result = request::with_retries(command.run(args.output, &settings.download_dir)).await;
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            std::process::exit(cli::exit_code(&e));
+        }
+        return Ok(());
+    }
+
+    let base_url = match settings.connection.base_url.clone() {
+        Some(base_url) => base_url,
+        None => {
+            println!(r#"
          _         _  __                                   _         _
         | |__   __| |/ _|___   _ __  _ __ _____  ___   _  | |_ _   _(_)
         | '_ \ / _` | |_/ __| | '_ \| '__/ _ \ \/ / | | | | __| | | | |
@@ -22,21 +47,26 @@ async fn main() -> Result<()> {
         |_| |_|\__,_|_| |___/ | .__/|_|  \___/_/\_\\__, |  \__|\__,_|_|
                               |_|                  |___/
                               "#);
-        std::process::exit(0);
-    }
-    let base_url = args
-        .get(1)
-        .expect(&"can not found the base url".to_string())
-        .to_string();
+            println!("{}", Args::command().render_help());
+            println!("No base url, pass one or set `base_url` in the config file.");
+            std::process::exit(0);
+        }
+    };
+
+    // Configure log, before loading the connection to show its warnings
+    let log_level = settings.log_level.unwrap_or(LevelFilter::Debug);
+    tui_logger::init_logger(log_level).unwrap();
+    tui_logger::set_default_level(log_level);
+
+    use_connection(&settings.connection, true);
 
     // We need to share the App between thread
-    let app = Arc::new(tokio::sync::Mutex::new(App::new(sync_io_tx.clone())));
+    let app = Arc::new(tokio::sync::Mutex::new(App::new(
+        sync_io_tx.clone(),
+        &settings,
+    )));
     let app_ui = Arc::clone(&app);
 
-    // Configure log
-    tui_logger::init_logger(LevelFilter::Debug).unwrap();
-    tui_logger::set_default_level(log::LevelFilter::Debug);
-
     // Handle IO in a specifc thread
     tokio::spawn(async move {
         let mut handler = IoAsyncHandler::new(app);
@@ -45,6 +75,34 @@ async fn main() -> Result<()> {
         }
     });
 
-    start_ui(&app_ui, base_url).await?;
+    start_ui(&app_ui, base_url, settings.tick_rate).await?;
     Ok(())
 }
+
+/// Send the requests with the client of `connection` and sign them with its credentials,
+/// when they can not be loaded ask them on the terminal if `interactive`, else exit
+fn use_connection(connection: &Connection, interactive: bool) {
+    match connection.client() {
+        Ok(client) => {
+            request::set_client(client);
+            request::set_retry(connection.retry.clone());
+        }
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            std::process::exit(2);
+        }
+    }
+    let error = match connection.credentials() {
+        Ok(credentials) => return request::set_credentials(credentials),
+        Err(e) => e,
+    };
+    eprintln!("error: {:#}", error);
+    if interactive && std::io::stdin().is_terminal() {
+        match login::prompt_credentials(connection) {
+            Ok(Some(credentials)) => return request::set_credentials(credentials),
+            Ok(None) => {}
+            Err(e) => eprintln!("error: {:#}", e),
+        }
+    }
+    std::process::exit(2);
+}
@@ -0,0 +1,96 @@
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::SystemTime;
+
+use log::warn;
+use reqwest::header::HeaderValue;
+
+/// Seconds to add to the local clock to get the one of the proxy
+static OFFSET: AtomicI64 = AtomicI64::new(0);
+
+/// The skew below which the clocks are considered in sync, the `Date` header has a one second
+/// resolution and the response takes some time to come back
+const TOLERANCE: i64 = 2;
+
+/// The time stamp to sign a request with, in seconds, corrected with the clock of the proxy
+pub fn now() -> u64 {
+    let local = seconds(SystemTime::now());
+    (local + OFFSET.load(Ordering::Relaxed)).max(0) as u64
+}
+
+/// Forget the clock of the previous proxy
+pub fn reset() {
+    OFFSET.store(0, Ordering::Relaxed);
+}
+
+/// Compare the `Date` of a response rejected by the proxy with the local clock,
+/// returns true when the requests should be signed again with the new offset
+pub fn correct(date: Option<&HeaderValue>) -> bool {
+    let offset = match date.and_then(|date| offset_from(date, SystemTime::now())) {
+        Some(offset) => offset,
+        None => return false,
+    };
+    let previous = OFFSET.load(Ordering::Relaxed);
+    if (offset - previous).abs() <= TOLERANCE {
+        return false;
+    }
+    OFFSET.store(offset, Ordering::Relaxed);
+    let direction = if offset > 0 { "behind" } else { "ahead of" };
+    warn!(
+        "🕰️ the local clock is {}s {} the proxy, signing with the proxy time",
+        offset.abs(),
+        direction
+    );
+    true
+}
+
+/// The seconds between the `Date` header of the proxy and the local clock
+fn offset_from(date: &HeaderValue, local: SystemTime) -> Option<i64> {
+    let server = httpdate::parse_http_date(date.to_str().ok()?).ok()?;
+    Some(seconds(server) - seconds(local))
+}
+
+fn seconds(time: SystemTime) -> i64 {
+    match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(e) => -(e.duration().as_secs() as i64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn should_compute_offset_from_date() {
+        // Sun, 06 Nov 1994 08:49:37 GMT
+        let server = SystemTime::UNIX_EPOCH + Duration::from_secs(784111777);
+        let date = HeaderValue::from_static("Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(offset_from(&date, server), Some(0));
+        assert_eq!(
+            offset_from(&date, server + Duration::from_secs(300)),
+            Some(-300)
+        );
+        assert_eq!(
+            offset_from(&date, server - Duration::from_secs(90)),
+            Some(90)
+        );
+        assert_eq!(
+            offset_from(&HeaderValue::from_static("yesterday"), server),
+            None
+        );
+    }
+
+    #[test]
+    fn should_correct_only_once() {
+        let _globals = crate::request::GLOBALS.blocking_lock();
+        let ahead = SystemTime::now() + Duration::from_secs(600);
+        let date = HeaderValue::from_str(&httpdate::fmt_http_date(ahead)).unwrap();
+        assert!(correct(Some(&date)));
+        assert!((now() as i64 - seconds(SystemTime::now()) - 600).abs() <= TOLERANCE);
+        // a second rejection is not because of the clock
+        assert!(!correct(Some(&date)));
+        assert!(!correct(None));
+        reset();
+    }
+}
@@ -0,0 +1,173 @@
+use std::path::Path;
+use std::process::Command;
+
+use eyre::{eyre, Result, WrapErr};
+use log::warn;
+use serde::Deserialize;
+
+use super::negotiate::Negotiate;
+use super::signer::Signer;
+use super::verify::AccessKey;
+
+/// The environment variable holding the account, when the profile sets none
+pub const ACCOUNT_VAR: &str = "HDFS_PROXY_ACCOUNT";
+/// The environment variable holding the key, when the profile sets none
+pub const KEY_VAR: &str = "HDFS_PROXY_KEY";
+
+/// How the requests are authenticated
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthScheme {
+    /// An `auth-token` header signed with the key of the account
+    #[default]
+    Token,
+    /// Kerberos (SPNEGO) tokens in an `Authorization` header, see `Negotiate`
+    Negotiate,
+    /// No authentication, for an open proxy
+    None,
+}
+
+/// The account and the key the requests are signed with
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Credentials {
+    pub account: String,
+    pub key: String,
+    pub auth: AuthScheme,
+    pub signer: Signer,
+    /// The token helper, with the `Negotiate` scheme
+    pub negotiate: Option<Negotiate>,
+}
+
+impl Credentials {
+    /// Read the account and the key from an access file, signed with the default signer,
+    /// warning when other users can read it
+    pub fn from_access_file(path: &Path, auth: AuthScheme) -> Result<Self> {
+        if readable_by_others(path) {
+            warn!(
+                "🔑 the access file {} is readable by other users, restrict it with `chmod 600`",
+                path.display()
+            );
+        }
+        let access_key = AccessKey::load(path)?;
+        Ok(Self {
+            account: access_key.account,
+            key: access_key.key,
+            auth,
+            signer: Signer::default(),
+            negotiate: None,
+        })
+    }
+
+    /// Write the account and the key to an access file, which only the user can read
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let access_key = AccessKey {
+            account: self.account.clone(),
+            key: self.key.clone(),
+        };
+        access_key.save(path)
+    }
+}
+
+/// The non empty value of the environment variable `name`
+pub fn from_env(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .filter(|value| !value.trim().is_empty())
+}
+
+/// Run a helper printing the key on its standard output, like `pass show hdfs/prod`
+pub fn run_key_command(command: &str) -> Result<String> {
+    run_helper("key command", command, &[])
+}
+
+/// Run a helper printing a secret on its standard output, the `envs` are added to its environment
+pub(crate) fn run_helper(name: &str, command: &str, envs: &[(&str, &Path)]) -> Result<String> {
+    let mut args = command.split_whitespace();
+    let program = args.next().ok_or_else(|| eyre!("the {} is empty", name))?;
+    let output = Command::new(program)
+        .args(args)
+        .envs(envs.iter().copied())
+        .output()
+        .wrap_err_with(|| format!("can not run the {} {:?}", name, program))?;
+    if !output.status.success() {
+        return Err(eyre!(
+            "the {} {:?} failed with {}: {}",
+            name,
+            program,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    let secret = String::from_utf8(output.stdout)
+        .wrap_err_with(|| format!("the {} {:?} printed invalid text", name, program))?;
+    let secret = secret.trim_end_matches(['\r', '\n']);
+    if secret.is_empty() {
+        return Err(eyre!("the {} {:?} printed nothing", name, program));
+    }
+    Ok(secret.to_string())
+}
+
+/// Whether the file can be read by any user
+#[cfg(unix)]
+pub fn readable_by_others(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|metadata| metadata.permissions().mode() & 0o004 != 0)
+        .unwrap_or(false)
+}
+
+/// Permissions are not checked on this platform
+#[cfg(not(unix))]
+pub fn readable_by_others(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn should_run_key_command() {
+        assert_eq!(run_key_command("echo s3cret").unwrap(), "s3cret");
+        assert!(run_key_command("false").is_err());
+        assert!(run_key_command("true").is_err());
+        assert!(run_key_command(" ").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_detect_world_readable_file() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("access-{}.toml", std::process::id()));
+        std::fs::write(&path, "account = \"alice\"\nkey = \"s3cret\"\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(readable_by_others(&path));
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+        assert!(!readable_by_others(&path));
+
+        let credentials = Credentials::from_access_file(&path, AuthScheme::Token).unwrap();
+        assert_eq!(credentials.account, "alice");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn should_save_private_access_file() {
+        let path = std::env::temp_dir().join(format!("saved-{}.toml", std::process::id()));
+        let credentials = Credentials {
+            account: "alice".to_string(),
+            key: "s3\"cret".to_string(),
+            auth: AuthScheme::Token,
+            signer: Signer::default(),
+            negotiate: None,
+        };
+        credentials.save(&path).unwrap();
+        assert!(!readable_by_others(&path));
+        let loaded = Credentials::from_access_file(&path, AuthScheme::Token).unwrap();
+        assert_eq!(loaded, credentials);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+
+use reqwest::StatusCode;
+
+/// The result of a request to the proxy
+pub type Result<T> = std::result::Result<T, RequestError>;
+
+/// Why a request to the proxy failed, worded to be shown as is in the logs and popups
+#[derive(Debug)]
+pub enum RequestError {
+    /// 401, the credentials are missing, wrong or expired
+    AuthFailed { url: String },
+    /// 403, the account may not access the entry
+    PermissionDenied { url: String },
+    /// 404
+    NotFound { url: String },
+    /// Another 4xx status
+    Rejected { url: String, status: StatusCode },
+    /// A 5xx status, the proxy or the cluster behind it failed
+    Server { url: String, status: StatusCode },
+    /// The proxy answered something else than the expected JSON, e.g. an HTML page
+    Decode {
+        url: String,
+        source: serde_json::Error,
+    },
+    /// The proxy answered `{"boolean": false}`, e.g. to delete a missing path
+    NotDone { url: String, op: &'static str },
+    /// No answer: the connection failed or timed out
+    Http(reqwest::Error),
+    /// The local file to send could not be read
+    Local {
+        path: std::path::PathBuf,
+        source: std::io::Error,
+    },
+    /// The HTTP client could not be built, e.g. no TLS backend
+    Client(eyre::Report),
+}
+
+impl RequestError {
+    /// The error of a response with a non 2xx `status`, `None` for a success
+    pub fn from_status(url: &str, status: StatusCode) -> Option<Self> {
+        let url = url.to_string();
+        match status.as_u16() {
+            401 => Some(Self::AuthFailed { url }),
+            403 => Some(Self::PermissionDenied { url }),
+            404 => Some(Self::NotFound { url }),
+            _ if status.is_server_error() => Some(Self::Server { url, status }),
+            _ if status.is_client_error() => Some(Self::Rejected { url, status }),
+            _ => None,
+        }
+    }
+
+    /// The HTTP status answered by the proxy, when there is one
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::AuthFailed { .. } => Some(StatusCode::UNAUTHORIZED),
+            Self::PermissionDenied { .. } => Some(StatusCode::FORBIDDEN),
+            Self::NotFound { .. } => Some(StatusCode::NOT_FOUND),
+            Self::Rejected { status, .. } | Self::Server { status, .. } => Some(*status),
+            Self::Decode { .. } | Self::NotDone { .. } | Self::Local { .. } | Self::Client(_) => {
+                None
+            }
+            Self::Http(e) => e.status(),
+        }
+    }
+
+    /// Whether the proxy could not be reached at all
+    pub fn is_unreachable(&self) -> bool {
+        matches!(self, Self::Http(e) if e.is_connect() || e.is_timeout())
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AuthFailed { url } => write!(
+                f,
+                "authentication failed for {}, check the credentials of the connection",
+                url
+            ),
+            Self::PermissionDenied { url } => write!(f, "permission denied on {}", url),
+            Self::NotFound { url } => write!(f, "no such file or directory: {}", url),
+            Self::Rejected { url, status } => write!(f, "{} rejected the request: {}", url, status),
+            Self::Server { url, status } => write!(f, "the proxy failed on {}: {}", url, status),
+            Self::Decode { url, source } => {
+                write!(f, "unexpected answer from {}: {}", url, source)
+            }
+            Self::NotDone { url, op } => write!(f, "the proxy did not {} {}", op, url),
+            Self::Http(e) if e.is_timeout() => write!(f, "the proxy did not answer in time: {}", e),
+            Self::Http(e) if e.is_connect() => write!(f, "can not reach the proxy: {}", e),
+            Self::Http(e) => write!(f, "{}", e),
+            Self::Local { path, source } => {
+                write!(f, "can not read {}: {}", path.display(), source)
+            }
+            Self::Client(e) => write!(f, "can not build the HTTP client: {:#}", e),
+        }
+    }
+}
+
+impl std::error::Error for RequestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode { source, .. } => Some(source),
+            Self::Local { source, .. } => Some(source),
+            Self::Http(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RequestError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_type_statuses() {
+        let url = "http://localhost:7878/user/alice/";
+        assert!(RequestError::from_status(url, StatusCode::OK).is_none());
+        assert!(matches!(
+            RequestError::from_status(url, StatusCode::UNAUTHORIZED),
+            Some(RequestError::AuthFailed { .. })
+        ));
+        assert!(matches!(
+            RequestError::from_status(url, StatusCode::FORBIDDEN),
+            Some(RequestError::PermissionDenied { .. })
+        ));
+        let error = RequestError::from_status(url, StatusCode::NOT_FOUND).unwrap();
+        assert_eq!(
+            error.to_string(),
+            "no such file or directory: http://localhost:7878/user/alice/"
+        );
+        let error = RequestError::from_status(url, StatusCode::BAD_GATEWAY).unwrap();
+        assert!(matches!(error, RequestError::Server { .. }));
+        assert_eq!(error.status(), Some(StatusCode::BAD_GATEWAY));
+    }
+}
@@ -0,0 +1,127 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use eyre::{eyre, Result, WrapErr};
+use log::warn;
+use reqwest::{Certificate, Client, Identity, Proxy};
+use serde::Deserialize;
+
+/// The seconds to wait for a connection when the profile sets none
+const CONNECT_TIMEOUT: u64 = 10;
+
+/// The options of the HTTP client, configured per profile:
+///
+/// ```toml
+/// [profile.prod.http]
+/// connect_timeout = 5
+/// timeout = 300
+/// ca_file = "/etc/ssl/corp-ca.pem"
+/// client_cert = "/etc/hdfs-proxy-tui/alice.pem"
+/// client_key = "/etc/hdfs-proxy-tui/alice.key"
+/// proxy = "http://squid.corp:3128"
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Http {
+    /// Seconds to establish a connection, 10 by default
+    pub connect_timeout: Option<u64>,
+    /// Seconds for a whole request, unlimited by default as downloads can be long
+    pub timeout: Option<u64>,
+    /// A PEM bundle of CA certificates trusted along with the system ones
+    pub ca_file: Option<PathBuf>,
+    /// A PEM certificate chain and its PKCS #8 key authenticating the client
+    pub client_cert: Option<PathBuf>,
+    pub client_key: Option<PathBuf>,
+    /// Accept invalid certificates, for test clusters only
+    #[serde(default)]
+    pub insecure: bool,
+    /// The HTTP proxy, from the `HTTPS_PROXY` and `HTTP_PROXY` variables by default
+    pub proxy: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Http {
+    /// The client of the connection, shared by all its requests to reuse the connections
+    pub fn client(&self) -> Result<Client> {
+        let user_agent = self
+            .user_agent
+            .clone()
+            .unwrap_or_else(|| format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")));
+        let connect_timeout = self.connect_timeout.unwrap_or(CONNECT_TIMEOUT);
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .connect_timeout(Duration::from_secs(connect_timeout));
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        if let Some(ca_file) = &self.ca_file {
+            let bundle = read(ca_file, "CA bundle")?;
+            for certificate in Certificate::from_pem_bundle(&bundle)
+                .wrap_err_with(|| format!("invalid CA bundle {}", ca_file.display()))?
+            {
+                builder = builder.add_root_certificate(certificate);
+            }
+        }
+        match (&self.client_cert, &self.client_key) {
+            (Some(cert), Some(key)) => {
+                let identity = Identity::from_pkcs8_pem(
+                    &read(cert, "client certificate")?,
+                    &read(key, "client key")?,
+                )
+                .wrap_err("invalid client certificate or key")?;
+                builder = builder.identity(identity);
+            }
+            (None, None) => {}
+            _ => return Err(eyre!("client_cert and client_key go together")),
+        }
+        if self.insecure {
+            warn!("🔓 the certificates of the proxy are not verified");
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        if let Some(proxy) = &self.proxy {
+            let proxy =
+                Proxy::all(proxy).wrap_err_with(|| format!("invalid HTTP proxy {:?}", proxy))?;
+            builder = builder.proxy(proxy);
+        }
+        builder.build().wrap_err("can not build the HTTP client")
+    }
+}
+
+fn read(path: &Path, what: &str) -> Result<Vec<u8>> {
+    std::fs::read(path).wrap_err_with(|| format!("can not read the {} {}", what, path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_build_client() {
+        assert!(Http::default().client().is_ok());
+        let http: Http = toml::from_str(
+            r#"
+            connect_timeout = 5
+            timeout = 300
+            insecure = true
+            proxy = "http://squid.corp:3128"
+            user_agent = "etl/1.0"
+            "#,
+        )
+        .unwrap();
+        assert!(http.client().is_ok());
+    }
+
+    #[test]
+    fn should_reject_invalid_options() {
+        let http = Http {
+            client_cert: Some(PathBuf::from("alice.pem")),
+            ..Http::default()
+        };
+        assert!(http.client().is_err());
+        let http = Http {
+            ca_file: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..Http::default()
+        };
+        let error = http.client().unwrap_err();
+        assert!(error.to_string().contains("CA bundle"));
+    }
+}
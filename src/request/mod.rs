@@ -1,19 +1,126 @@
+use crate::app::properties::FileStatus;
 use crate::app::state::Item;
-use crate::crypto::mac::Mac;
-use crypto::hmac::Hmac;
-use crypto::sha1::Sha1;
-use lazy_static::lazy_static;
 use log::{error, info, warn};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
-use reqwest::Result;
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, DATE,
+};
+use reqwest::{Body, Client, Method, Response, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::SystemTime;
+use std::path::Path;
+use std::sync::RwLock;
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+mod clock;
+pub mod credentials;
+pub mod error;
+pub mod http;
+pub mod negotiate;
+pub mod retry;
+pub mod signer;
+#[cfg(test)]
+pub(crate) mod stand_in;
 mod verify;
-lazy_static! {
-    //try to load the user name and password from the access.toml
 
-    //TODO: cusomize the path of the access.toml
-    static ref USER_CONFIG:verify::AccessKey = verify::AccessKey::new("access.toml");
+pub use self::credentials::{AuthScheme, Credentials};
+pub use self::error::{RequestError, Result};
+use self::retry::Retry;
+use self::signer::Signer;
+
+/// Held by the tests changing the process wide state of the requests (credentials, clock offset),
+/// as the tests run in parallel
+#[cfg(test)]
+pub(crate) static GLOBALS: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+/// The credentials of the active connection
+static CREDENTIALS: RwLock<Option<Credentials>> = RwLock::new(None);
+
+/// Sign the next requests with `credentials`, e.g. when switching to another connection
+pub fn set_credentials(credentials: Credentials) {
+    *CREDENTIALS.write().expect("credentials lock poisoned") = Some(credentials);
+    negotiate::forget_tokens();
+    clock::reset();
+}
+
+/// The HTTP client of the active connection
+static CLIENT: RwLock<Option<Client>> = RwLock::new(None);
+
+/// Send the next requests with `client`, built from the options of the connection
+pub fn set_client(client: Client) {
+    *CLIENT.write().expect("client lock poisoned") = Some(client);
+}
+
+/// The client of the active connection, one with the default options when none is set
+fn client() -> Result<Client> {
+    if let Some(client) = CLIENT.read().expect("client lock poisoned").as_ref() {
+        return Ok(client.clone());
+    }
+    let mut client = CLIENT.write().expect("client lock poisoned");
+    if let Some(client) = client.as_ref() {
+        return Ok(client.clone());
+    }
+    let default = http::Http::default()
+        .client()
+        .map_err(RequestError::Client)?;
+    Ok(client.insert(default).clone())
+}
+
+/// How the idempotent requests of the active connection are retried
+static RETRY: RwLock<Option<Retry>> = RwLock::new(None);
+
+/// Retry the next idempotent requests with `retry`
+pub fn set_retry(retry: Retry) {
+    *RETRY.write().expect("retry lock poisoned") = Some(retry);
+}
+
+/// Who waits for the requests, it decides how their idempotent ones are retried
+#[derive(Clone, Copy)]
+enum Scope {
+    /// The IO task or a subcommand, with the policy of the connection
+    Background,
+    /// The UI, with the screen held meanwhile
+    Interactive,
+}
+
+tokio::task_local! {
+    /// Set around the requests which are retried, the others are sent once
+    static RETRYING: Scope;
+}
+
+/// Run `future` with the idempotent requests retried with the policy of the connection
+pub async fn with_retries<F: std::future::Future>(future: F) -> F::Output {
+    RETRYING.scope(Scope::Background, future).await
+}
+
+/// Run `future`, awaited by the UI, with the idempotent requests retried once at most
+/// not to freeze the screen, unless it already runs with the policy of the connection
+pub async fn interactive<F: std::future::Future>(future: F) -> F::Output {
+    if RETRYING.try_with(|_| ()).is_ok() {
+        future.await
+    } else {
+        RETRYING.scope(Scope::Interactive, future).await
+    }
+}
+
+fn retry_policy() -> Retry {
+    RETRY
+        .read()
+        .expect("retry lock poisoned")
+        .clone()
+        .unwrap_or_default()
+}
+
+/// The credentials of the active connection, anonymous when none is set
+fn credentials() -> Credentials {
+    CREDENTIALS
+        .read()
+        .expect("credentials lock poisoned")
+        .clone()
+        .unwrap_or_else(|| Credentials {
+            auth: AuthScheme::None,
+            ..Credentials::default()
+        })
 }
 
 fn extract_path_and_host_from_url(url: &str) -> (String, String) {
@@ -29,85 +136,518 @@ fn extract_path_and_host_from_url(url: &str) -> (String, String) {
     return (host.to_string(), "/".to_owned() + &path);
 }
 
-fn extract_filename_from_url(url: &str) -> String {
-    let slash_index = url.rfind('/').expect("unknown state");
-    return url[slash_index..url.len()].to_owned();
+/// Split `url` into its origin (`http://host:port`) and its path (starting with a '/')
+pub fn split_origin(url: &str) -> (&str, &str) {
+    let host_start = url.find("://").map(|index| index + 3).unwrap_or(0);
+    match url[host_start..].find('/') {
+        Some(index) => url.split_at(host_start + index),
+        None => (url, "/"),
+    }
 }
 
-fn prepare(method: &str, path: &str, account: &str, passwd: &str) -> HeaderMap {
+/// The authentication headers of a request, the `query` is only signed when the signer includes it
+fn prepare(
+    signer: &Signer,
+    method: &str,
+    path: &str,
+    query: Option<&str>,
+    account: &str,
+    passwd: &str,
+) -> HeaderMap {
     let mut res = HeaderMap::new();
-    let time_stamp = SystemTime::now()
-        .duration_since(SystemTime::UNIX_EPOCH)
-        .expect("failed to get time stamp")
-        .as_secs();
-    let token = format!("{},{},{},{}", time_stamp, account, method, path);
-    let mut mac = Hmac::new(Sha1::new(), passwd.as_bytes());
-    mac.input(token.as_bytes());
-    let result = mac.result();
-    let hmac = result.code();
-    let sign = hex::encode(hmac);
-    res.insert(
-        HeaderName::from_static("auth-token"),
-        HeaderValue::from_str(&format!("{},{},{}", time_stamp, account, sign)).unwrap(),
-    );
-    return res;
+    let time_stamp = clock::now();
+    let token = signer.token(time_stamp, account, passwd, method, path, query);
+    match HeaderName::from_bytes(signer.header.as_bytes()) {
+        Ok(name) => match HeaderValue::from_str(&token) {
+            Ok(value) => {
+                res.insert(name, value);
+            }
+            Err(_) => error!("invalid auth token for the header {:?}", signer.header),
+        },
+        Err(_) => error!("invalid auth header name {:?}", signer.header),
+    }
+    res
 }
 
 pub async fn get_item_list(url: &str) -> Result<Vec<Item>> {
     info!("🛜 sending request to {}", url);
-    let (_, path) = extract_path_and_host_from_url(url);
-    let account = USER_CONFIG.account.as_str();
-    let passwd = USER_CONFIG.key.as_str();
-    let header = prepare("GET", &path, account, passwd);
-    let client = reqwest::Client::new();
-    let res = client.get(url).headers(header).send().await?;
-    let status = res.status();
-    info!("status:{:?}", status);
-    let body = res.text().await?;
-    let items: Vec<Item> = serde_json::from_str(&body).unwrap();
-    return Ok(items);
+    let res = send(Method::GET, url, NO_QUERY, None).await?;
+    let body = check(url, res)?.text().await?;
+    decode(url, &body)
 }
 
 pub async fn get_file_chunk(url: &str) -> Result<String> {
     info!("🛜 sending request to {}", url);
-    let (_, path) = extract_path_and_host_from_url(url);
-    let account = USER_CONFIG.account.as_str();
-    let passwd = USER_CONFIG.key.as_str();
-    let header = prepare("GET", &path, account, passwd);
-    let client = reqwest::Client::new();
-    let res = client.get(url).headers(header).send().await?;
-    let status = res.status();
-    info!("status:{:?}", status);
-    let body = res.text().await?;
+    let res = send(Method::GET, url, NO_QUERY, None).await?;
+    let body = check(url, res)?.text().await?;
     return Ok(body);
 }
 
-pub async fn dump_file(url: &str, file_chunk: &Option<String>) -> Result<()> {
-    info!("🦁️download file from {}", url);
-    if url.ends_with('/') {
-        warn!("trying to dump a directory, ignored.");
-        return Ok(());
+/// The response when its status is a success, its typed error otherwise
+fn check(url: &str, res: Response) -> Result<Response> {
+    info!("status:{:?}", res.status());
+    match RequestError::from_status(url, res.status()) {
+        Some(e) => Err(e),
+        None => Ok(res),
+    }
+}
+
+/// Decode the JSON `body` answered by `url`
+fn decode<T: DeserializeOwned>(url: &str, body: &str) -> Result<T> {
+    serde_json::from_str(body).map_err(|source| RequestError::Decode {
+        url: url.to_string(),
+        source,
+    })
+}
+
+/// The headers of a request signed with the credentials of the active connection
+fn signed_headers(method: &str, url: &str, query: Option<&str>) -> HeaderMap {
+    let credentials = credentials();
+    match credentials.auth {
+        AuthScheme::Token => {
+            let (_, path) = extract_path_and_host_from_url(url);
+            prepare(
+                &credentials.signer,
+                method,
+                &path,
+                query,
+                &credentials.account,
+                &credentials.key,
+            )
+        }
+        AuthScheme::Negotiate => {
+            let mut res = HeaderMap::new();
+            let authorization = match &credentials.negotiate {
+                Some(negotiate) => negotiate.authorization(url),
+                None => Err(eyre::eyre!("no negotiate command")),
+            };
+            match authorization {
+                Ok(authorization) => {
+                    res.insert(AUTHORIZATION, authorization);
+                }
+                Err(e) => error!("🎫 sending the request without token: {:#}", e),
+            }
+            res
+        }
+        AuthScheme::None => HeaderMap::new(),
+    }
+}
+
+/// Get the raw content of a file, binary files included
+pub async fn get_file_bytes(url: &str) -> Result<Vec<u8>> {
+    info!("🛜 sending request to {}", url);
+    let res = send(Method::GET, url, NO_QUERY, None).await?;
+    let body = check(url, res)?.bytes().await?;
+    Ok(body.to_vec())
+}
+
+/// The response to a request of the file at `url`, its content is not read yet so it can be streamed
+pub async fn get_file_stream(url: &str) -> Result<Response> {
+    info!("🛜 sending request to {}", url);
+    let res = send(Method::GET, url, NO_QUERY, None).await?;
+    check(url, res)
+}
+
+/// Create or replace the file at `url`
+pub async fn put_file(url: &str, content: Vec<u8>) -> Result<()> {
+    info!("🛜 uploading {} bytes to {}", content.len(), url);
+    let res = send(Method::PUT, url, NO_QUERY, Some(&Payload::Bytes(&content))).await?;
+    check(url, res)?;
+    Ok(())
+}
+
+/// Download the remote file at `url` to the local `dest` file
+pub async fn download_file(url: &str, dest: &Path) -> eyre::Result<()> {
+    let mut res = get_file_stream(url).await?;
+    let mut file = tokio::fs::File::create(dest).await?;
+    while let Some(chunk) = res.chunk().await.map_err(RequestError::from)? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    info!("⬇️ {} downloaded to {}", url, dest.display());
+    Ok(())
+}
+
+/// Upload the local `src` file to the remote `url`
+pub async fn upload_file(src: &Path, url: &str) -> eyre::Result<()> {
+    info!("🛜 uploading {} to {}", src.display(), url);
+    let res = send(Method::PUT, url, NO_QUERY, Some(&Payload::File(src))).await?;
+    check(url, res)?;
+    info!("⬆️ {} uploaded to {}", src.display(), url);
+    Ok(())
+}
+
+/// Send a signed request for an operation on `url`, the parameters are in the query string
+async fn send_op<Q>(method: Method, url: &str, query: &Q) -> Result<Response>
+where
+    Q: Serialize + std::fmt::Debug + ?Sized,
+{
+    info!("🛜 sending {} request to {} {:?}", method, url, query);
+    let res = send(method, url, query, None).await?;
+    check(url, res)
+}
+
+/// The answer of the operations which may not be done, e.g. DELETE on a missing path
+#[derive(Deserialize)]
+struct Done {
+    boolean: bool,
+}
+
+/// Send an operation answering `{"boolean": …}`, it failed when the proxy answered false
+async fn send_boolean_op<Q>(method: Method, url: &str, query: &Q, op: &'static str) -> Result<()>
+where
+    Q: Serialize + std::fmt::Debug + ?Sized,
+{
+    let res = send_op(method, url, query).await?;
+    let body = res.text().await?;
+    let done: Done = decode(url, &body)?;
+    if done.boolean {
+        Ok(())
+    } else {
+        Err(RequestError::NotDone {
+            url: url.to_string(),
+            op,
+        })
+    }
+}
+
+/// A request without parameters
+const NO_QUERY: &[(&str, &str)] = &[];
+
+/// Send a signed request, the idempotent (GET) ones are retried after transient failures
+async fn send<Q>(
+    method: Method,
+    url: &str,
+    query: &Q,
+    body: Option<&Payload<'_>>,
+) -> Result<Response>
+where
+    Q: Serialize + ?Sized,
+{
+    let client = client()?;
+    let retry = match RETRYING.try_with(|scope| *scope) {
+        Ok(Scope::Background) => retry_policy(),
+        Ok(Scope::Interactive) => retry_policy().interactive(),
+        Err(_) => Retry {
+            max_attempts: 1,
+            ..Retry::default()
+        },
+    };
+    let attempts = if method == Method::GET {
+        retry.max_attempts.max(1)
+    } else {
+        1
+    };
+    let mut attempt = 1;
+    loop {
+        let result = send_signed(&client, method.clone(), url, query, body).await;
+        if attempt >= attempts || !retry::is_transient(&result) {
+            return result;
+        }
+        let delay = retry.delay(attempt);
+        let failure = match &result {
+            Ok(res) => res.status().to_string(),
+            Err(e) => e.to_string(),
+        };
+        warn!(
+            "🔁 {} {} failed ({}), retry {}/{} in {:?}",
+            method,
+            url,
+            failure,
+            attempt,
+            attempts - 1,
+            delay
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// What the requests of a connection are sent with, to switch back to it
+pub struct Session {
+    credentials: Option<Credentials>,
+    client: Option<Client>,
+    retry: Option<Retry>,
+}
+
+/// The session of the active connection
+pub fn session() -> Session {
+    Session {
+        credentials: CREDENTIALS
+            .read()
+            .expect("credentials lock poisoned")
+            .clone(),
+        client: CLIENT.read().expect("client lock poisoned").clone(),
+        retry: RETRY.read().expect("retry lock poisoned").clone(),
+    }
+}
+
+/// Send the next requests with `session` again, e.g. when a new connection failed
+pub fn restore(session: Session) {
+    *CREDENTIALS.write().expect("credentials lock poisoned") = session.credentials;
+    *CLIENT.write().expect("client lock poisoned") = session.client;
+    *RETRY.write().expect("retry lock poisoned") = session.retry;
+    negotiate::forget_tokens();
+    clock::reset();
+}
+
+/// Send a signed request, it is signed and sent again when the proxy rejected its cached
+/// negotiate token, or rejected it and its `Date` shows that the local clock drifted
+async fn send_signed<Q>(
+    client: &Client,
+    method: Method,
+    url: &str,
+    query: &Q,
+    body: Option<&Payload<'_>>,
+) -> Result<Response>
+where
+    Q: Serialize + ?Sized,
+{
+    let mut res = send_once(client, method.clone(), url, query, body).await?;
+    if res.status() == StatusCode::UNAUTHORIZED && negotiate::forget_token(url) {
+        info!(
+            "🎫 token rejected, sending {} request to {} with a new one",
+            method, url
+        );
+        res = send_once(client, method.clone(), url, query, body).await?;
     }
+    let rejected = matches!(
+        res.status(),
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN
+    );
+    if rejected && clock::correct(res.headers().get(DATE)) {
+        info!("🛜 sending {} request to {} again", method, url);
+        return send_once(client, method, url, query, body).await;
+    }
+    Ok(res)
+}
+
+async fn send_once<Q>(
+    client: &Client,
+    method: Method,
+    url: &str,
+    query: &Q,
+    body: Option<&Payload<'_>>,
+) -> Result<Response>
+where
+    Q: Serialize + ?Sized,
+{
+    let mut builder = client.request(method.clone(), url).query(query);
+    match body {
+        Some(Payload::Bytes(bytes)) => builder = builder.body(bytes.to_vec()),
+        Some(Payload::File(path)) => {
+            let local = |source| RequestError::Local {
+                path: path.to_path_buf(),
+                source,
+            };
+            let file = tokio::fs::File::open(path).await.map_err(local)?;
+            let len = file.metadata().await.map_err(local)?.len();
+            builder = builder
+                .header(CONTENT_LENGTH, len)
+                .body(Body::wrap_stream(ReaderStream::new(file)));
+        }
+        None => {}
+    }
+    let mut request = builder.build()?;
+    let header = signed_headers(method.as_str(), url, request.url().query());
+    request.headers_mut().extend(header);
+    Ok(client.execute(request).await?)
+}
 
-    info!("target url:{}", url);
-    let file_name = extract_filename_from_url(url);
+/// The content sent with a request
+enum Payload<'a> {
+    Bytes(&'a [u8]),
+    /// Streamed from a local file, it is opened again when the request is sent again
+    File(&'a Path),
+}
 
-    if let Some(file_chunk_str) = file_chunk {
-        std::fs::write(&file_name, file_chunk_str)
-            .expect(&format!("failed to write to {} from {}", &file_name, url));
-        return Ok(());
+/// Delete the file or directory at `url`, a non empty directory needs `recursive`
+pub async fn delete(url: &str, recursive: bool) -> Result<()> {
+    let recursive = if recursive { "true" } else { "false" };
+    let query = [("op", "DELETE"), ("recursive", recursive)];
+    send_boolean_op(Method::DELETE, url, &query, "DELETE").await
+}
+
+/// Create the directory at `url`, along with its missing parents
+pub async fn mkdirs(url: &str) -> Result<()> {
+    send_boolean_op(Method::PUT, url, &[("op", "MKDIRS")], "MKDIRS").await
+}
+
+/// Rename (or move) the entry at `url` to the `destination` path, on the same proxy
+pub async fn rename(url: &str, destination: &str) -> Result<()> {
+    let query = [("op", "RENAME"), ("destination", destination)];
+    send_boolean_op(Method::PUT, url, &query, "RENAME").await
+}
+
+/// The permission, owner, group and replication of the entry at `url`
+pub async fn get_file_status(url: &str) -> Result<FileStatus> {
+    /// The answer of GETFILESTATUS, `{"FileStatus": {…}}`
+    #[derive(Deserialize)]
+    struct Wrapper {
+        #[serde(rename = "FileStatus")]
+        status: FileStatus,
     }
 
-    let chunk = get_file_chunk(url).await?;
-    std::fs::write(&file_name, chunk)
-        .expect(&format!("failed to write to {} from {}", &file_name, url));
-    return Ok(());
+    let res = send_op(Method::GET, url, &[("op", "GETFILESTATUS")]).await?;
+    let body = res.text().await?;
+    let wrapper: Wrapper = decode(url, &body)?;
+    Ok(wrapper.status)
+}
+
+/// Change a property of the entry at `url`, the query holds the operation and its parameters
+pub async fn set_property(url: &str, query: &[(&str, String)]) -> Result<()> {
+    send_op(Method::PUT, url, query).await?;
+    Ok(())
+}
+
+/// Where `path` goes in the trash of `account`, following the HDFS convention
+pub fn trash_path(path: &str, account: &str) -> String {
+    format!(
+        "/user/{}/.Trash/Current{}",
+        account,
+        path.trim_end_matches('/')
+    )
+}
+
+/// Move the entry at `url` to the trash of the user, returns its path in the trash
+pub async fn move_to_trash(url: &str) -> Result<String> {
+    let (origin, path) = split_origin(url);
+    let trash = trash_path(path, &credentials().account);
+    let (parent, _) = trash.split_at(trash.rfind('/').unwrap_or(0));
+    mkdirs(&format!("{}{}/", origin, parent)).await?;
+    rename(url, &trash).await?;
+    Ok(trash)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::stand_in::{header, response, stand_in_proxy};
     use super::*;
 
+    #[test]
+    fn should_split_origin() {
+        assert_eq!(
+            split_origin("http://localhost:7878/user/hive/"),
+            ("http://localhost:7878", "/user/hive/")
+        );
+        assert_eq!(split_origin("https://host"), ("https://host", "/"));
+    }
+
+    #[test]
+    fn should_build_trash_path() {
+        assert_eq!(
+            trash_path("/warehouse/sales/", "alice"),
+            "/user/alice/.Trash/Current/warehouse/sales"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_type_failed_listings() {
+        let origin = stand_in_proxy(|_| response("404 Not Found", "<h1>404</h1>\n")).await;
+        let url = format!("{}/user/alice/", origin);
+        let error = get_item_list(&url).await.unwrap_err();
+        assert!(matches!(error, RequestError::NotFound { .. }));
+
+        let origin = stand_in_proxy(|_| response("200 OK", "<h1>200</h1>\n")).await;
+        let url = format!("{}/user/alice/", origin);
+        let error = get_item_list(&url).await.unwrap_err();
+        assert!(matches!(error, RequestError::Decode { .. }));
+    }
+
+    #[tokio::test]
+    async fn should_restore_the_previous_session() {
+        let _globals = GLOBALS.lock().await;
+        let initial = session();
+        let previous = Credentials {
+            account: "alice".to_string(),
+            ..Credentials::default()
+        };
+        set_credentials(previous.clone());
+        let session = session();
+        set_credentials(Credentials {
+            account: "bob".to_string(),
+            ..Credentials::default()
+        });
+        restore(session);
+        assert_eq!(credentials(), previous);
+        restore(initial);
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_not_deleted() {
+        let origin = stand_in_proxy(|head| match head.contains("recursive=true") {
+            true => response("200 OK", r#"{"boolean":true}"#),
+            false => response("200 OK", r#"{"boolean":false}"#),
+        })
+        .await;
+        let url = format!("{}/user/alice/logs/", origin);
+        delete(&url, true).await.unwrap();
+        let error = delete(&url, false).await.unwrap_err();
+        assert!(matches!(error, RequestError::NotDone { op: "DELETE", .. }));
+    }
+
+    #[tokio::test]
+    async fn should_decode_file_status() {
+        let origin = stand_in_proxy(|_| {
+            response(
+                "200 OK",
+                r#"{"FileStatus":{"accessTime":0,"blockSize":134217728,"group":"hadoop",
+                "length":24930,"modificationTime":1320173277227,"owner":"hive",
+                "pathSuffix":"","permission":"644","replication":3,"type":"FILE"}}"#,
+            )
+        })
+        .await;
+        let url = format!("{}/user/hive/data.csv", origin);
+        let status = get_file_status(&url).await.unwrap();
+        assert_eq!(status.permission, "644");
+        assert_eq!(status.owner, "hive");
+        assert_eq!(status.group, "hadoop");
+        assert_eq!(status.replication, 3);
+    }
+
+    #[tokio::test]
+    async fn should_stream_transfers() {
+        let origin = stand_in_proxy(|head| match head.split_whitespace().next() {
+            Some("PUT") if header(head, "content-length") == Some("11") => {
+                response("201 Created", "")
+            }
+            Some("GET") => response("200 OK", "hello world"),
+            _ => response("400 Bad Request", ""),
+        })
+        .await;
+        let url = format!("{}/user/alice/hello.txt", origin);
+        let dir = std::env::temp_dir().join(format!("hdfs-proxy-tui-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let local = dir.join("hello.txt");
+
+        download_file(&url, &local).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&local).unwrap(), "hello world");
+        upload_file(&local, &url).await.unwrap();
+        let error = upload_file(&dir.join("missing.txt"), &url)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<RequestError>(),
+            Some(RequestError::Local { .. })
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn should_fail_when_not_moved() {
+        let origin = stand_in_proxy(|head| match head.contains("op=MKDIRS") {
+            true => response("200 OK", r#"{"boolean":true}"#),
+            false => response("200 OK", r#"{"boolean":false}"#),
+        })
+        .await;
+        let url = format!("{}/user/alice/logs/", origin);
+        mkdirs(&url).await.unwrap();
+        let error = rename(&url, "/user/alice/old/").await.unwrap_err();
+        assert!(matches!(error, RequestError::NotDone { op: "RENAME", .. }));
+        let error = move_to_trash(&url).await.unwrap_err();
+        assert!(matches!(error, RequestError::NotDone { op: "RENAME", .. }));
+    }
+
     #[tokio::test]
     async fn success_get_item_list() -> Result<()> {
         let list = get_item_list("http:localhost:7878/Users/march1917/").await;
@@ -0,0 +1,181 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use eyre::{eyre, Result};
+use reqwest::header::HeaderValue;
+use reqwest::Url;
+use serde::Deserialize;
+
+use super::credentials;
+
+/// Kerberos authentication (SPNEGO), configured per profile with `auth = "negotiate"`:
+///
+/// ```toml
+/// [profile.secure.negotiate]
+/// command = "spnego-token HTTP@{host}"
+/// ticket_cache = "/tmp/krb5cc_1000"
+/// ```
+///
+/// The GSSAPI exchange is left to a helper command printing a base64 token for the service
+/// of the proxy, `{host}` being replaced by its host name. It finds the credentials in the
+/// ticket cache, or in the keytab, both passed through the usual Kerberos variables.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Negotiate {
+    pub command: String,
+    /// Passed to the command as `KRB5_CLIENT_KTNAME`
+    pub keytab: Option<PathBuf>,
+    /// Passed to the command as `KRB5CCNAME`
+    pub ticket_cache: Option<PathBuf>,
+}
+
+impl Negotiate {
+    /// The `Authorization` header of a request to `url`, the token of its host is asked to the
+    /// command once, then reused until the proxy rejects it
+    pub fn authorization(&self, url: &str) -> Result<HeaderValue> {
+        let host = host(url)?;
+        let mut tokens = TOKENS.lock().expect("tokens lock poisoned");
+        if let Some((_, authorization)) = tokens.iter().find(|(cached, _)| *cached == host) {
+            return Ok(authorization.clone());
+        }
+        let token =
+            credentials::run_helper("negotiate command", &self.command_line(url)?, &self.envs())?;
+        let token = token.trim();
+        let authorization = HeaderValue::from_str(&format!("Negotiate {}", token))
+            .map_err(|_| eyre!("the negotiate command printed an invalid token"))?;
+        tokens.push((host, authorization.clone()));
+        Ok(authorization)
+    }
+
+    fn command_line(&self, url: &str) -> Result<String> {
+        Ok(self.command.replace("{host}", &host(url)?))
+    }
+
+    fn envs(&self) -> Vec<(&str, &Path)> {
+        let mut envs = Vec::new();
+        if let Some(keytab) = &self.keytab {
+            envs.push(("KRB5_CLIENT_KTNAME", keytab.as_path()));
+        }
+        if let Some(ticket_cache) = &self.ticket_cache {
+            envs.push(("KRB5CCNAME", ticket_cache.as_path()));
+        }
+        envs
+    }
+}
+
+/// The tokens of the active connection by host, the command is run for each request otherwise
+static TOKENS: Mutex<Vec<(String, HeaderValue)>> = Mutex::new(Vec::new());
+
+/// Forget the token of the host of `url` after the proxy rejected it,
+/// returns whether there was one
+pub fn forget_token(url: &str) -> bool {
+    let host = match host(url) {
+        Ok(host) => host,
+        Err(_) => return false,
+    };
+    let mut tokens = TOKENS.lock().expect("tokens lock poisoned");
+    let len = tokens.len();
+    tokens.retain(|(cached, _)| *cached != host);
+    tokens.len() < len
+}
+
+/// Forget every token, e.g. when switching to another connection
+pub fn forget_tokens() {
+    TOKENS.lock().expect("tokens lock poisoned").clear();
+}
+
+fn host(url: &str) -> Result<String> {
+    let url = Url::parse(url)?;
+    let host = url.host_str().ok_or_else(|| eyre!("no host in {}", url))?;
+    Ok(host.to_string())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::request::stand_in::{header, response, stand_in_proxy};
+    use crate::request::{get_file_bytes, set_credentials, AuthScheme, Credentials, GLOBALS};
+
+    /// A stand-in for a SPNEGO protected proxy: it challenges the requests without token
+    /// and only accepts the `token` one
+    async fn negotiate_proxy(token: &'static str) -> String {
+        let origin = stand_in_proxy(move |head| match header(head, "authorization") {
+            Some(received) if received == format!("Negotiate {}", token) => {
+                response("200 OK", "ok")
+            }
+            _ => response("401 Unauthorized\r\nwww-authenticate: Negotiate", ""),
+        })
+        .await;
+        format!("{}/user/alice/data.csv", origin)
+    }
+
+    fn negotiate(command: &str) -> Credentials {
+        Credentials {
+            auth: AuthScheme::Negotiate,
+            negotiate: Some(Negotiate {
+                command: command.to_string(),
+                keytab: None,
+                ticket_cache: Some(PathBuf::from("/tmp/krb5cc_test")),
+            }),
+            ..Credentials::default()
+        }
+    }
+
+    #[test]
+    fn should_build_command_line() {
+        let negotiate = negotiate("spnego-token HTTP@{host}").negotiate.unwrap();
+        assert_eq!(
+            negotiate
+                .command_line("http://proxy.prod:7878/user/")
+                .unwrap(),
+            "spnego-token HTTP@proxy.prod"
+        );
+        assert_eq!(
+            negotiate.envs(),
+            vec![("KRB5CCNAME", Path::new("/tmp/krb5cc_test"))]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_authenticate_with_stand_in_proxy() {
+        let _globals = GLOBALS.lock().await;
+        let url = negotiate_proxy("YWxpY2U=").await;
+
+        set_credentials(negotiate("echo YWxpY2U="));
+        assert_eq!(get_file_bytes(&url).await.unwrap(), b"ok");
+
+        set_credentials(negotiate("echo Ym9i"));
+        let error = get_file_bytes(&url).await.unwrap_err();
+        assert_eq!(error.status().map(|status| status.as_u16()), Some(401));
+    }
+
+    #[tokio::test]
+    async fn should_reuse_token_until_rejected() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let _globals = GLOBALS.lock().await;
+        let url = negotiate_proxy("bmV3").await;
+        // the helper prints a rejected token first, then the accepted one
+        let dir =
+            std::env::temp_dir().join(format!("hdfs-proxy-tui-negotiate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let runs = dir.join("runs");
+        let helper = dir.join("helper.sh");
+        std::fs::write(
+            &helper,
+            format!(
+                "#!/bin/sh\necho run >> {runs}\n\
+                 if [ $(wc -l < {runs}) -eq 1 ]; then echo b2xk; else echo bmV3; fi\n",
+                runs = runs.display()
+            ),
+        )
+        .unwrap();
+        std::fs::set_permissions(&helper, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        set_credentials(negotiate(&helper.display().to_string()));
+        for _ in 0..3 {
+            assert_eq!(get_file_bytes(&url).await.unwrap(), b"ok");
+        }
+        assert_eq!(std::fs::read_to_string(&runs).unwrap().lines().count(), 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use rand::Rng;
+use reqwest::Response;
+use serde::Deserialize;
+
+/// How the idempotent requests (listings, reads and stats) are retried on connection
+/// errors, timeouts and 5xx responses, configured per profile:
+///
+/// ```toml
+/// [profile.prod.retry]
+/// max_attempts = 5
+/// base_delay = 500
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Retry {
+    /// Attempts of a request, the first one included, 1 disables the retries
+    pub max_attempts: u32,
+    /// Milliseconds before the first retry, doubled for each next one
+    pub base_delay: u64,
+    /// The longest delay in milliseconds
+    pub max_delay: u64,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: 200,
+            max_delay: 5000,
+        }
+    }
+}
+
+impl Retry {
+    /// The policy of the requests of the UI, which waits for them:
+    /// one retry at most, after the base delay at most
+    pub fn interactive(&self) -> Self {
+        Self {
+            max_attempts: self.max_attempts.min(2),
+            base_delay: self.base_delay,
+            max_delay: self.max_delay.min(self.base_delay),
+        }
+    }
+
+    /// The delay before the retry following the `attempt` (from 1), picked at random
+    /// between the half and the whole exponential delay so that clients do not retry in sync
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exponential = self
+            .base_delay
+            .saturating_mul(1 << (attempt - 1).min(16))
+            .min(self.max_delay);
+        let jittered = rand::thread_rng().gen_range(exponential / 2..=exponential);
+        Duration::from_millis(jittered)
+    }
+}
+
+/// Whether a failed request may succeed when sent again
+pub fn is_transient(result: &super::Result<Response>) -> bool {
+    match result {
+        Ok(res) => res.status().is_server_error(),
+        Err(e) => e.is_unreachable(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_back_off_exponentially() {
+        let retry = Retry::default();
+        for _ in 0..20 {
+            let first = retry.delay(1);
+            assert!(first >= Duration::from_millis(100) && first <= Duration::from_millis(200));
+            let third = retry.delay(3);
+            assert!(third >= Duration::from_millis(400) && third <= Duration::from_millis(800));
+            let capped = retry.delay(40);
+            assert!(capped >= Duration::from_millis(2500) && capped <= Duration::from_millis(5000));
+        }
+    }
+
+    #[test]
+    fn should_load_from_config() {
+        let retry: Retry = toml::from_str("max_attempts = 5").unwrap();
+        assert_eq!(retry.max_attempts, 5);
+        assert_eq!(retry.base_delay, 200);
+    }
+
+    #[test]
+    fn should_cap_interactive_retries() {
+        let retry = Retry {
+            max_attempts: 5,
+            base_delay: 500,
+            max_delay: 5000,
+        }
+        .interactive();
+        assert_eq!(retry.max_attempts, 2);
+        assert!(retry.delay(1) <= Duration::from_millis(500));
+        let disabled = Retry {
+            max_attempts: 1,
+            ..Retry::default()
+        };
+        assert_eq!(disabled.interactive().max_attempts, 1);
+    }
+}
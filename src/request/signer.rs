@@ -0,0 +1,169 @@
+use crypto::hmac::Hmac;
+use crypto::mac::Mac;
+use crypto::sha1::Sha1;
+use crypto::sha2::Sha256;
+use reqwest::header::HeaderName;
+use serde::{Deserialize, Deserializer};
+
+/// The hash function of the HMAC
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Algorithm {
+    #[default]
+    HmacSha1,
+    HmacSha256,
+}
+
+/// How a request is signed, configured per profile:
+///
+/// ```toml
+/// [profile.prod.signer]
+/// algorithm = "hmac-sha256"
+/// header = "x-auth-token"
+/// include_query = true
+/// ```
+///
+/// The header holds `timestamp,account,signature`, the signature being the hex encoded HMAC
+/// of `timestamp,account,method,path` with the key, `path?query` when the query is included.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Signer {
+    pub algorithm: Algorithm,
+    #[serde(deserialize_with = "header_name")]
+    pub header: String,
+    /// Sign the query string along with the path
+    pub include_query: bool,
+}
+
+impl Default for Signer {
+    /// The signature of the first proxies
+    fn default() -> Self {
+        Self {
+            algorithm: Algorithm::HmacSha1,
+            header: String::from("auth-token"),
+            include_query: false,
+        }
+    }
+}
+
+impl Signer {
+    /// The header value authenticating a request sent at `time_stamp` (in seconds)
+    pub fn token(
+        &self,
+        time_stamp: u64,
+        account: &str,
+        key: &str,
+        method: &str,
+        path: &str,
+        query: Option<&str>,
+    ) -> String {
+        let signed = match query {
+            Some(query) if self.include_query && !query.is_empty() => {
+                format!("{},{},{},{}?{}", time_stamp, account, method, path, query)
+            }
+            _ => format!("{},{},{},{}", time_stamp, account, method, path),
+        };
+        let code = match self.algorithm {
+            Algorithm::HmacSha1 => hmac(Hmac::new(Sha1::new(), key.as_bytes()), &signed),
+            Algorithm::HmacSha256 => hmac(Hmac::new(Sha256::new(), key.as_bytes()), &signed),
+        };
+        format!("{},{},{}", time_stamp, account, hex::encode(code))
+    }
+}
+
+fn hmac<M: Mac>(mut mac: M, message: &str) -> Vec<u8> {
+    mac.input(message.as_bytes());
+    mac.result().code().to_vec()
+}
+
+/// Reject the header names reqwest can not send when the config is loaded
+fn header_name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let name = String::deserialize(deserializer)?;
+    HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| serde::de::Error::custom(format!("invalid header name {:?}", name)))?;
+    Ok(name.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIME_STAMP: u64 = 1700000000;
+
+    fn token(signer: &Signer, method: &str, path: &str, query: Option<&str>) -> String {
+        signer.token(TIME_STAMP, "alice", "s3cret", method, path, query)
+    }
+
+    #[test]
+    fn should_sign_with_sha1() {
+        let signer = Signer::default();
+        assert_eq!(
+            token(&signer, "GET", "/user/alice/data.csv", None),
+            "1700000000,alice,ff9e74187ac91edb99d8cf8daf4597f6cdefe2aa"
+        );
+        // the query is ignored by default
+        assert_eq!(
+            token(
+                &signer,
+                "GET",
+                "/user/alice/data.csv",
+                Some("op=GETFILESTATUS")
+            ),
+            "1700000000,alice,ff9e74187ac91edb99d8cf8daf4597f6cdefe2aa"
+        );
+    }
+
+    #[test]
+    fn should_sign_with_sha256() {
+        let signer = Signer {
+            algorithm: Algorithm::HmacSha256,
+            ..Signer::default()
+        };
+        assert_eq!(
+            token(&signer, "GET", "/user/alice/data.csv", None),
+            "1700000000,alice,4318ba5a6611f06be75137cc1fd05bfae5cdfdbb0ad68c372809ed28c5c1b5fd"
+        );
+    }
+
+    #[test]
+    fn should_sign_query() {
+        let signer = Signer {
+            include_query: true,
+            ..Signer::default()
+        };
+        assert_eq!(
+            token(&signer, "PUT", "/user/alice/dir/", Some("op=MKDIRS")),
+            "1700000000,alice,fe33c8826f7a36026fd36faf4d0c94915ddbf9f8"
+        );
+        assert_eq!(
+            token(&signer, "GET", "/user/alice/data.csv", Some("")),
+            "1700000000,alice,ff9e74187ac91edb99d8cf8daf4597f6cdefe2aa"
+        );
+
+        let signer = Signer {
+            algorithm: Algorithm::HmacSha256,
+            include_query: true,
+            ..Signer::default()
+        };
+        assert_eq!(
+            token(&signer, "PUT", "/user/alice/dir/", Some("op=MKDIRS")),
+            "1700000000,alice,093396fd251868ddb5751d87468a5519c54d6ebb4352ebb37568e361f387bb7f"
+        );
+    }
+
+    #[test]
+    fn should_load_from_config() {
+        let signer: Signer = toml::from_str(
+            r#"
+            algorithm = "hmac-sha256"
+            header = "X-Auth-Token"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(signer.algorithm, Algorithm::HmacSha256);
+        assert_eq!(signer.header, "x-auth-token");
+        assert!(!signer.include_query);
+        assert!(toml::from_str::<Signer>(r#"header = "auth token""#).is_err());
+        assert!(toml::from_str::<Signer>(r#"algorithm = "md5""#).is_err());
+    }
+}
@@ -0,0 +1,49 @@
+//! A stand-in proxy for the tests, listening on a local port
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Start a proxy answering each request with `respond`, called with the head of the request
+/// (its request line and headers), returns the origin of the proxy
+pub async fn stand_in_proxy<F>(respond: F) -> String
+where
+    F: Fn(&str) -> String + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        loop {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut head = Vec::new();
+            let mut buffer = [0; 1024];
+            while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+                let read = stream.read(&mut buffer).await.unwrap();
+                if read == 0 {
+                    break;
+                }
+                head.extend_from_slice(&buffer[..read]);
+            }
+            let response = respond(&String::from_utf8_lossy(&head));
+            stream.write_all(response.as_bytes()).await.unwrap();
+        }
+    });
+    format!("http://localhost:{}", address.port())
+}
+
+/// A response with the `status` line (e.g. "404 Not Found") and the `body`
+pub fn response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+/// The value of the header `name` in the `head` of a request
+pub fn header<'a>(head: &'a str, name: &str) -> Option<&'a str> {
+    head.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        Some(value.trim()).filter(|_| key.eq_ignore_ascii_case(name))
+    })
+}
@@ -1,16 +1,35 @@
+use std::io::Write;
+use std::path::Path;
+
+use eyre::WrapErr;
 use toml;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct AccessKey{
     pub account:String,
     pub key:String,
 }
 
 impl AccessKey{
-    pub fn new(path:&str) -> AccessKey{
-       let path_str = std::fs::read_to_string(path).expect("Unable to read config file");
-       let access_key = toml::from_str::<AccessKey>(&path_str).unwrap(); 
-       return access_key;
+    pub fn load(path:&Path) -> eyre::Result<AccessKey>{
+       let path_str = std::fs::read_to_string(path)
+           .wrap_err_with(|| format!("can not read the access file {}", path.display()))?;
+       let access_key = toml::from_str::<AccessKey>(&path_str)
+           .wrap_err_with(|| format!("invalid access file {}, expected `account` and `key`", path.display()))?;
+       return Ok(access_key);
+    }
+
+    /// Write the access file, only readable by the user on unix
+    pub fn save(&self, path:&Path) -> eyre::Result<()>{
+       let content = toml::to_string(self)?;
+       let mut options = std::fs::OpenOptions::new();
+       options.write(true).create(true).truncate(true);
+       #[cfg(unix)]
+       std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+       let mut file = options.open(path)
+           .wrap_err_with(|| format!("can not write the access file {}", path.display()))?;
+       file.write_all(content.as_bytes())?;
+       Ok(())
     }
 }